@@ -1,8 +1,15 @@
+use base64::Engine;
 use solana_account::Account;
+use solana_address::Address;
 use solana_pubkey::Pubkey;
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
 
 pub const SYSTEM_PROGRAM_ID: Pubkey = solana_pubkey::pubkey!("11111111111111111111111111111111");
 
+pub const SYSVAR_PROGRAM_ID: Pubkey =
+    solana_pubkey::pubkey!("Sysvar1111111111111111111111111111111111111");
+
 pub fn system_account_with_lamports(lamports: u64) -> Account {
     Account::new(lamports, 0, &SYSTEM_PROGRAM_ID)
 }
@@ -16,3 +23,144 @@ pub fn program_account(owner: &Pubkey, data: Vec<u8>) -> Account {
         rent_epoch: 0,
     }
 }
+
+pub fn executable_account(loader: &Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        executable: true,
+        ..program_account(loader, data)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountTemplate {
+    lamports: u64,
+    owner: Pubkey,
+    data: Vec<u8>,
+    executable: bool,
+}
+
+impl AccountTemplate {
+    pub fn new() -> Self {
+        Self {
+            owner: SYSTEM_PROGRAM_ID,
+            ..Self::default()
+        }
+    }
+
+    pub fn lamports(mut self, lamports: u64) -> Self {
+        self.lamports = lamports;
+        self
+    }
+
+    pub fn owner(mut self, owner: Pubkey) -> Self {
+        self.owner = owner;
+        self
+    }
+
+    pub fn data(mut self, data: Vec<u8>) -> Self {
+        self.data = data;
+        self
+    }
+
+    pub fn executable(mut self, executable: bool) -> Self {
+        self.executable = executable;
+        self
+    }
+
+    pub fn build(&self) -> Account {
+        Account {
+            lamports: self.lamports,
+            data: self.data.clone(),
+            owner: self.owner,
+            executable: self.executable,
+            rent_epoch: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountDiff {
+    pub pubkey: Pubkey,
+    pub old_lamports: u64,
+    pub new_lamports: u64,
+    pub data_changed: bool,
+}
+
+impl AccountDiff {
+    pub fn lamport_delta(&self) -> i128 {
+        self.new_lamports as i128 - self.old_lamports as i128
+    }
+}
+
+pub(crate) fn is_runtime_account(account: &Account) -> bool {
+    account.executable || account.owner == SYSVAR_PROGRAM_ID
+}
+
+pub(crate) fn diff_accounts(
+    before: &HashMap<Address, Account>,
+    after: &HashMap<Address, Account>,
+) -> Vec<AccountDiff> {
+    let addresses: BTreeSet<&Address> = before.keys().chain(after.keys()).collect();
+
+    addresses
+        .into_iter()
+        .filter_map(|address| {
+            let old = before.get(address);
+            let new = after.get(address);
+            if old == new || old.into_iter().chain(new).any(is_runtime_account) {
+                return None;
+            }
+            Some(AccountDiff {
+                pubkey: Pubkey::from(address.to_bytes()),
+                old_lamports: old.map_or(0, |a| a.lamports),
+                new_lamports: new.map_or(0, |a| a.lamports),
+                data_changed: old.map(|a| &a.data) != new.map(|a| &a.data),
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn account_from_json(value: &serde_json::Value) -> Result<Account, String> {
+    let field = |name: &str| {
+        value
+            .get(name)
+            .ok_or_else(|| format!("missing field {name}"))
+    };
+
+    let lamports = field("lamports")?
+        .as_u64()
+        .ok_or_else(|| "invalid lamports".to_string())?;
+    let owner = field("owner")?
+        .as_str()
+        .and_then(|owner| Pubkey::from_str(owner).ok())
+        .ok_or_else(|| "invalid owner".to_string())?;
+    let executable = field("executable")?
+        .as_bool()
+        .ok_or_else(|| "invalid executable".to_string())?;
+    let rent_epoch = field("rentEpoch")?
+        .as_u64()
+        .ok_or_else(|| "invalid rentEpoch".to_string())?;
+    let data = field("data")?;
+    if let Some(encoding) = data
+        .get(1)
+        .and_then(|encoding| encoding.as_str())
+        .filter(|encoding| *encoding != "base64")
+    {
+        return Err(format!("unsupported data encoding {encoding}"));
+    }
+    let data = data
+        .get(0)
+        .and_then(|data| data.as_str())
+        .ok_or_else(|| "invalid data".to_string())?;
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| e.to_string())?;
+
+    Ok(Account {
+        lamports,
+        data,
+        owner,
+        executable,
+        rent_epoch,
+    })
+}
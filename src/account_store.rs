@@ -17,10 +17,18 @@ impl InMemoryAccountStore {
         self.accounts.insert(address, account);
     }
 
+    pub fn remove(&mut self, address: &Address) -> Option<Account> {
+        self.accounts.remove(address)
+    }
+
     pub fn get_balance(&self, address: &Address) -> Option<u64> {
         self.accounts.get(address).map(|a| a.lamports)
     }
 
+    pub fn accounts(&self) -> impl Iterator<Item = (&Address, &Account)> {
+        self.accounts.iter()
+    }
+
     pub fn snapshot(&self) -> HashMap<Address, Account> {
         self.accounts.clone()
     }
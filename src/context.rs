@@ -1,23 +1,35 @@
-use crate::account;
+use crate::account::{self, AccountDiff, AccountTemplate};
 use crate::account_store::InMemoryAccountStore;
 use crate::error::{MolluskHelperError, Result};
-use crate::token;
-use crate::transaction::TransactionBuilder;
+use crate::program_cache::ProgramCache;
+use crate::sysvars::SysvarsSummary;
+use crate::token::{self, MintInfo, TokenAccountInfo, TokenProgram};
+use crate::transaction::{TransactionBuilder, TransactionResult};
+use base64::Engine;
 use mollusk_svm::account_store::AccountStore;
 use mollusk_svm::result::{InstructionResult, ProgramResult};
+use mollusk_svm::sysvar::Sysvars;
 use mollusk_svm::{Mollusk, MolluskContext};
 use solana_account::Account;
 use solana_address::Address;
 use solana_hash::Hash;
-use solana_instruction::Instruction;
+use solana_instruction::error::InstructionError;
+use solana_instruction::{AccountMeta, Instruction};
 use solana_keypair::Keypair;
+use solana_loader_v3_interface::state::UpgradeableLoaderState;
 use solana_message::{v0, AddressLookupTableAccount, VersionedMessage};
+use solana_program_error::ProgramError;
 use solana_program_pack::Pack;
 use solana_pubkey::Pubkey;
+use solana_rent::Rent;
 use solana_signer::Signer;
-use spl_token::state::Account as TokenAccount;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use solana_slot_hashes::SlotHashes;
+use solana_svm_log_collector::LogCollector;
+use solana_transaction::versioned::VersionedTransaction;
+use spl_token::state::{Account as TokenAccount, Mint};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, PoisonError, RwLock};
 
 pub const MEMO_PROGRAM_ID: Pubkey =
     solana_pubkey::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
@@ -34,23 +46,72 @@ pub const ADDRESS_LOOKUP_TABLE_PROGRAM_ID: Pubkey =
 pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
     solana_pubkey::pubkey!("ComputeBudget111111111111111111111111111111");
 
+pub const DEFAULT_LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+const DEFAULT_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProgramLoader {
     V2,
     V3,
 }
 
+fn loader_key(loader: ProgramLoader) -> &'static Pubkey {
+    match loader {
+        ProgramLoader::V2 => &mollusk_svm::program::loader_keys::LOADER_V2,
+        ProgramLoader::V3 => &mollusk_svm::program::loader_keys::LOADER_V3,
+    }
+}
+
+const DEFAULT_PROGRAMS: [(Pubkey, fn(&mut Mollusk)); 5] = [
+    (
+        mollusk_svm_programs_token::token::ID,
+        mollusk_svm_programs_token::token::add_program,
+    ),
+    (
+        mollusk_svm_programs_token::associated_token::ID,
+        mollusk_svm_programs_token::associated_token::add_program,
+    ),
+    (
+        mollusk_svm_programs_token::token2022::ID,
+        mollusk_svm_programs_token::token2022::add_program,
+    ),
+    (
+        mollusk_svm_programs_memo::memo::ID,
+        mollusk_svm_programs_memo::memo::add_program,
+    ),
+    (
+        mollusk_svm_programs_memo::memo_v1::ID,
+        mollusk_svm_programs_memo::memo_v1::add_program,
+    ),
+];
+
 fn add_default_programs(mollusk: &mut Mollusk) {
-    mollusk_svm_programs_token::token::add_program(mollusk);
-    mollusk_svm_programs_token::associated_token::add_program(mollusk);
-    mollusk_svm_programs_token::token2022::add_program(mollusk);
-    mollusk_svm_programs_memo::memo::add_program(mollusk);
-    mollusk_svm_programs_memo::memo_v1::add_program(mollusk);
+    add_default_programs_except(mollusk, &HashSet::new());
+}
+
+fn add_default_programs_except(mollusk: &mut Mollusk, removed: &HashSet<Pubkey>) {
+    for (program_id, add_program) in DEFAULT_PROGRAMS {
+        if !removed.contains(&program_id) {
+            add_program(mollusk);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserHandle {
+    pub pubkey: Pubkey,
+    pub ata: Pubkey,
 }
 
 pub struct MolluskContextHelper {
-    pub(crate) context: MolluskContext<InMemoryAccountStore>,
+    pub(crate) context: RefCell<MolluskContext<InMemoryAccountStore>>,
     keypairs: Arc<RwLock<HashMap<String, Keypair>>>,
+    checkpoints: Arc<RwLock<HashMap<String, HashMap<Address, Account>>>>,
+    programs: Vec<(Pubkey, ProgramLoader, Arc<[u8]>)>,
+    program_names: HashMap<Pubkey, String>,
+    removed_programs: HashSet<Pubkey>,
+    initial_unix_timestamp: i64,
+    frozen_unix_timestamp: Option<i64>,
 }
 
 impl MolluskContextHelper {
@@ -63,6 +124,12 @@ impl MolluskContextHelper {
         )
     }
 
+    pub fn new_named(name: &str, program_id: &Pubkey, elf_bytes: &[u8]) -> Self {
+        let mut helper = Self::new(program_id, elf_bytes);
+        helper.program_names.insert(*program_id, name.to_string());
+        helper
+    }
+
     pub fn new_with_timestamp(program_id: &Pubkey, elf_bytes: &[u8], unix_timestamp: u64) -> Self {
         Self::new_with_options(program_id, elf_bytes, ProgramLoader::V3, unix_timestamp)
     }
@@ -82,29 +149,54 @@ impl MolluskContextHelper {
         loader: ProgramLoader,
         unix_timestamp: u64,
     ) -> Self {
-        let mut mollusk = Mollusk::default();
+        Self::new_with_elf(program_id, Arc::from(elf_bytes), loader, unix_timestamp)
+    }
 
-        let loader_key = match loader {
-            ProgramLoader::V2 => &mollusk_svm::program::loader_keys::LOADER_V2,
-            ProgramLoader::V3 => &mollusk_svm::program::loader_keys::LOADER_V3,
-        };
+    fn new_with_elf(
+        program_id: &Pubkey,
+        elf_bytes: Arc<[u8]>,
+        loader: ProgramLoader,
+        unix_timestamp: u64,
+    ) -> Self {
+        let mut mollusk = Mollusk::default();
 
         mollusk.add_program_with_loader_and_elf(
             &Self::pubkey_to_address(program_id),
-            loader_key,
-            elf_bytes,
+            loader_key(loader),
+            &elf_bytes,
         );
 
         add_default_programs(&mut mollusk);
         mollusk.sysvars.clock.unix_timestamp = unix_timestamp as i64;
+        mollusk.logger = Some(LogCollector::new_ref());
 
         let store = InMemoryAccountStore::new();
-        let context = mollusk.with_context(store);
+        let context = RefCell::new(mollusk.with_context(store));
 
-        Self {
+        let helper = Self {
             context,
             keypairs: Arc::new(RwLock::new(HashMap::new())),
-        }
+            checkpoints: Arc::new(RwLock::new(HashMap::new())),
+            programs: vec![(*program_id, loader, elf_bytes)],
+            program_names: HashMap::new(),
+            removed_programs: HashSet::new(),
+            initial_unix_timestamp: unix_timestamp as i64,
+            frozen_unix_timestamp: None,
+        };
+        helper.ensure_sysvar_accounts();
+        helper
+    }
+
+    pub fn new_with_cached_program(cache: &ProgramCache, program_id: &Pubkey) -> Result<Self> {
+        let (loader, elf_bytes) = cache
+            .get(program_id)
+            .ok_or_else(|| MolluskHelperError::ProgramNotLoaded(program_id.to_string()))?;
+        Ok(Self::new_with_elf(
+            program_id,
+            elf_bytes,
+            loader,
+            Self::current_unix_timestamp(),
+        ))
     }
 
     pub fn new_without_program() -> Self {
@@ -116,13 +208,95 @@ impl MolluskContextHelper {
 
         add_default_programs(&mut mollusk);
         mollusk.sysvars.clock.unix_timestamp = unix_timestamp as i64;
+        mollusk.logger = Some(LogCollector::new_ref());
 
         let store = InMemoryAccountStore::new();
-        let context = mollusk.with_context(store);
+        let context = RefCell::new(mollusk.with_context(store));
 
-        Self {
+        let helper = Self {
             context,
             keypairs: Arc::new(RwLock::new(HashMap::new())),
+            checkpoints: Arc::new(RwLock::new(HashMap::new())),
+            programs: Vec::new(),
+            program_names: HashMap::new(),
+            removed_programs: HashSet::new(),
+            initial_unix_timestamp: unix_timestamp as i64,
+            frozen_unix_timestamp: None,
+        };
+        helper.ensure_sysvar_accounts();
+        helper
+    }
+
+    pub fn fork(&self) -> Self {
+        let mollusk = self.rebuild_mollusk();
+
+        let store = self.context.borrow().account_store.borrow().clone();
+        let context = RefCell::new(mollusk.with_context(store));
+
+        let keypairs = self
+            .keypairs
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(name, keypair)| (name.clone(), keypair.insecure_clone()))
+            .collect();
+
+        let checkpoints = self
+            .checkpoints
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+
+        Self {
+            context,
+            keypairs: Arc::new(RwLock::new(keypairs)),
+            checkpoints: Arc::new(RwLock::new(checkpoints)),
+            programs: self.programs.clone(),
+            program_names: self.program_names.clone(),
+            removed_programs: self.removed_programs.clone(),
+            initial_unix_timestamp: self.initial_unix_timestamp,
+            frozen_unix_timestamp: self.frozen_unix_timestamp,
+        }
+    }
+
+    fn rebuild_mollusk(&self) -> Mollusk {
+        let mut mollusk = Mollusk::default();
+
+        for (program_id, loader, elf_bytes) in &self.programs {
+            mollusk.add_program_with_loader_and_elf(
+                &Self::pubkey_to_address(program_id),
+                loader_key(*loader),
+                elf_bytes,
+            );
+        }
+
+        add_default_programs_except(&mut mollusk, &self.removed_programs);
+
+        let context = self.context.borrow();
+        let sysvars = &context.mollusk.sysvars;
+        mollusk.sysvars.clock = sysvars.clock.clone();
+        mollusk.sysvars.epoch_rewards = sysvars.epoch_rewards.clone();
+        mollusk.sysvars.epoch_schedule = sysvars.epoch_schedule.clone();
+        mollusk.sysvars.last_restart_slot = sysvars.last_restart_slot.clone();
+        mollusk.sysvars.rent = sysvars.rent.clone();
+        mollusk.sysvars.slot_hashes = SlotHashes::new(&sysvars.slot_hashes);
+        mollusk.sysvars.stake_history = sysvars.stake_history.clone();
+        mollusk.compute_budget = context.mollusk.compute_budget;
+
+        let bytes_limit = context
+            .mollusk
+            .logger
+            .as_ref()
+            .and_then(|logger| logger.borrow().bytes_limit);
+        mollusk.logger = Some(LogCollector::new_ref_with_limit(bytes_limit));
+
+        mollusk
+    }
+
+    pub(crate) fn program_label(&self, program_id: &Pubkey) -> String {
+        match self.program_names.get(program_id) {
+            Some(name) => format!("program {name}"),
+            None => format!("program {program_id}"),
         }
     }
 
@@ -136,177 +310,1527 @@ impl MolluskContextHelper {
         elf_bytes: &[u8],
         loader: ProgramLoader,
     ) {
-        let loader_key = match loader {
-            ProgramLoader::V2 => &mollusk_svm::program::loader_keys::LOADER_V2,
-            ProgramLoader::V3 => &mollusk_svm::program::loader_keys::LOADER_V3,
-        };
-
-        self.context.mollusk.add_program_with_loader_and_elf(
-            &Self::pubkey_to_address(program_id),
-            loader_key,
-            elf_bytes,
+        self.context
+            .get_mut()
+            .mollusk
+            .add_program_with_loader_and_elf(
+                &Self::pubkey_to_address(program_id),
+                loader_key(loader),
+                elf_bytes,
+            );
+        self.programs
+            .push((*program_id, loader, Arc::from(elf_bytes)));
+        self.removed_programs.remove(program_id);
+    }
+
+    pub fn program_elf(&self, program_id: &Pubkey) -> Option<Arc<[u8]>> {
+        self.programs
+            .iter()
+            .rfind(|(id, _, _)| id == program_id)
+            .map(|(_, _, elf_bytes)| Arc::clone(elf_bytes))
+    }
+
+    pub fn remove_program(&mut self, program_id: &Pubkey) -> Result<()> {
+        let is_default = DEFAULT_PROGRAMS.iter().any(|(id, _)| id == program_id)
+            && !self.removed_programs.contains(program_id);
+        let is_added = self.programs.iter().any(|(id, _, _)| id == program_id);
+        if !is_default && !is_added {
+            return Err(MolluskHelperError::ProgramNotLoaded(program_id.to_string()));
+        }
+
+        self.programs.retain(|(id, _, _)| id != program_id);
+        self.removed_programs.insert(*program_id);
+        self.context
+            .get_mut()
+            .account_store
+            .borrow_mut()
+            .remove(&Self::pubkey_to_address(program_id));
+        Ok(())
+    }
+
+    pub fn add_program_with_upgrade_authority(
+        &mut self,
+        program_id: &Pubkey,
+        elf_bytes: &[u8],
+        upgrade_authority: &Pubkey,
+    ) {
+        self.add_program_with_loader(program_id, elf_bytes, ProgramLoader::V3);
+
+        let (program_account, mut programdata_account) =
+            mollusk_svm::program::create_program_account_pair_loader_v3(program_id, elf_bytes);
+        let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+        bincode::serialize_into(
+            &mut programdata_account.data[..metadata_len],
+            &UpgradeableLoaderState::ProgramData {
+                slot: 0,
+                upgrade_authority_address: Some(*upgrade_authority),
+            },
+        )
+        .unwrap();
+
+        self.add_account(program_id, program_account);
+        self.add_account(&Self::programdata_address(program_id), programdata_account);
+    }
+
+    pub fn get_upgrade_authority(&self, program_id: &Pubkey) -> Result<Option<Pubkey>> {
+        let program_account = self
+            .get_account(program_id)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(program_id.to_string()))?;
+        let programdata_address =
+            match bincode::deserialize::<UpgradeableLoaderState>(&program_account.data) {
+                Ok(UpgradeableLoaderState::Program {
+                    programdata_address,
+                }) => programdata_address,
+                _ => return Err(ProgramError::InvalidAccountData.into()),
+            };
+
+        let programdata_account = self
+            .get_account(&programdata_address)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(programdata_address.to_string()))?;
+        match bincode::deserialize::<UpgradeableLoaderState>(&programdata_account.data) {
+            Ok(UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address,
+                ..
+            }) => Ok(upgrade_authority_address),
+            _ => Err(ProgramError::InvalidAccountData.into()),
+        }
+    }
+
+    pub fn programdata_address(program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[program_id.as_ref()],
+            &mollusk_svm::program::loader_keys::LOADER_V3,
+        )
+        .0
+    }
+
+    pub fn current_unix_timestamp() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+    }
+
+    pub fn process_instruction(&self, instruction: &Instruction) -> Result<InstructionResult> {
+        if self.removed_programs.contains(&instruction.program_id) {
+            return Err(MolluskHelperError::ProgramNotLoaded(
+                instruction.program_id.to_string(),
+            ));
+        }
+        let result = self.process_instruction_internal(instruction);
+        if result.raw_result == Err(InstructionError::CallDepth) {
+            return Err(MolluskHelperError::CpiDepthExceeded(self.max_cpi_depth()));
+        }
+        match &result.program_result {
+            ProgramResult::Success => Ok(result),
+            ProgramResult::Failure(e) => Err(MolluskHelperError::ProgramError(e.clone())),
+            ProgramResult::UnknownError(e) => Err(MolluskHelperError::InstructionFailed(e.clone())),
+        }
+    }
+
+    pub fn process_instruction_with_logs(
+        &self,
+        instruction: &Instruction,
+    ) -> Result<(InstructionResult, Vec<String>)> {
+        let result = self.process_instruction(instruction)?;
+        Ok((result, self.logs()))
+    }
+
+    pub fn process_instruction_with_signers(
+        &self,
+        instruction: &Instruction,
+        signers: &[Pubkey],
+    ) -> Result<InstructionResult> {
+        let mut instruction = instruction.clone();
+        for meta in &mut instruction.accounts {
+            if signers.contains(&meta.pubkey) {
+                meta.is_signer = true;
+            }
+        }
+        self.process_instruction(&instruction)
+    }
+
+    pub fn process_instruction_as(
+        &self,
+        instruction: &Instruction,
+        payer: &Pubkey,
+    ) -> Result<InstructionResult> {
+        let snapshot = self.snapshot_accounts();
+        let result = self.process_instruction(instruction)?;
+        if let Err(e) = self.deduct_fee(payer, DEFAULT_LAMPORTS_PER_SIGNATURE) {
+            self.restore_accounts(snapshot);
+            return Err(e);
+        }
+        Ok(result)
+    }
+
+    pub fn process_instruction_with_changes(
+        &self,
+        instruction: &Instruction,
+    ) -> Result<(InstructionResult, Vec<AccountDiff>)> {
+        let before = self.snapshot_accounts();
+        let result = self.process_instruction(instruction)?;
+        let after = self.snapshot_accounts();
+        Ok((result, account::diff_accounts(&before, &after)))
+    }
+
+    pub fn process_instruction_capturing(
+        &self,
+        instruction: &Instruction,
+        pubkey: &Pubkey,
+    ) -> Result<(Account, Account)> {
+        let before = self.get_account(pubkey).unwrap_or_default();
+        self.process_instruction(instruction)?;
+        let after = self.get_account(pubkey).unwrap_or_default();
+        Ok((before, after))
+    }
+
+    pub fn process_instruction_expect_abort(&self, instruction: &Instruction) -> Result<()> {
+        let result = self.process_instruction_internal(instruction);
+        match result.raw_result {
+            Err(InstructionError::ProgramFailedToComplete) => Ok(()),
+            Err(e) => Err(MolluskHelperError::AssertionFailed(format!(
+                "expected program to abort, but it returned an error: {e}"
+            ))),
+            Ok(()) => Err(MolluskHelperError::AssertionFailed(
+                "expected program to abort, but it succeeded".to_string(),
+            )),
+        }
+    }
+
+    pub(crate) fn deduct_fee(&self, payer: &Pubkey, fee: u64) -> Result<()> {
+        let mut account = self
+            .get_account(payer)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(payer.to_string()))?;
+        if account.lamports < fee {
+            return Err(MolluskHelperError::InsufficientFunds {
+                have: account.lamports,
+                need: fee,
+            });
+        }
+        account.lamports -= fee;
+        self.add_account(payer, account);
+        Ok(())
+    }
+
+    pub fn process_instruction_unchecked(&self, instruction: &Instruction) -> InstructionResult {
+        self.process_instruction_internal(instruction)
+    }
+
+    pub(crate) fn process_instruction_internal(
+        &self,
+        instruction: &Instruction,
+    ) -> InstructionResult {
+        self.clear_logs();
+        if self.removed_programs.contains(&instruction.program_id) {
+            return InstructionResult {
+                program_result: ProgramResult::UnknownError(InstructionError::UnsupportedProgramId),
+                raw_result: Err(InstructionError::UnsupportedProgramId),
+                ..Default::default()
+            };
+        }
+        self.context.borrow().process_instruction(instruction)
+    }
+
+    pub(crate) fn process_instruction_with_compute_unit_limit(
+        &self,
+        instruction: &Instruction,
+        compute_unit_limit: u64,
+    ) -> InstructionResult {
+        let previous = std::mem::replace(
+            &mut self
+                .context
+                .borrow_mut()
+                .mollusk
+                .compute_budget
+                .compute_unit_limit,
+            compute_unit_limit,
+        );
+        let result = self.process_instruction_internal(instruction);
+        self.context
+            .borrow_mut()
+            .mollusk
+            .compute_budget
+            .compute_unit_limit = previous;
+        result
+    }
+
+    pub fn run_repeated(&self, instruction: &Instruction, times: usize) -> Vec<InstructionResult> {
+        (0..times)
+            .map(|_| self.process_instruction_internal(instruction))
+            .collect()
+    }
+
+    pub fn assert_deterministic(&self, instruction: &Instruction, runs: usize) -> Result<()> {
+        let mut expected = None;
+        for run in 0..runs {
+            let snapshot = self.snapshot_accounts();
+            let result = self.process_instruction_internal(instruction);
+            self.restore_accounts(snapshot);
+
+            let compute_units = result.compute_units_consumed;
+            match expected {
+                None => expected = Some(compute_units),
+                Some(first) if first != compute_units => {
+                    return Err(MolluskHelperError::AssertionFailed(format!(
+                        "run {run} consumed {compute_units} compute units, first run consumed {first}"
+                    )));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_max_cpi_depth(&mut self, depth: usize) {
+        self.context
+            .get_mut()
+            .mollusk
+            .compute_budget
+            .max_instruction_stack_depth = depth + 1;
+    }
+
+    pub fn reset_compute_meter(&mut self) {
+        self.context
+            .get_mut()
+            .mollusk
+            .compute_budget
+            .compute_unit_limit = DEFAULT_COMPUTE_UNIT_LIMIT;
+    }
+
+    pub fn max_cpi_depth(&self) -> usize {
+        self.context
+            .borrow()
+            .mollusk
+            .compute_budget
+            .max_instruction_stack_depth
+            .saturating_sub(1)
+    }
+
+    pub fn set_log_byte_limit(&mut self, bytes: usize) {
+        if let Some(logger) = &self.context.get_mut().mollusk.logger {
+            logger.borrow_mut().bytes_limit = Some(bytes);
+        }
+    }
+
+    pub fn logs(&self) -> Vec<String> {
+        self.context
+            .borrow()
+            .mollusk
+            .logger
+            .as_ref()
+            .map(|logger| logger.borrow().get_recorded_content().to_vec())
+            .unwrap_or_default()
+    }
+
+    pub fn anchor_events_from_logs(logs: &[String]) -> Vec<Vec<u8>> {
+        logs.iter()
+            .filter_map(|line| line.strip_prefix("Program data: "))
+            .filter_map(|fields| {
+                fields
+                    .split_whitespace()
+                    .map(|field| base64::engine::general_purpose::STANDARD.decode(field))
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .ok()
+                    .map(|chunks| chunks.concat())
+            })
+            .collect()
+    }
+
+    fn clear_logs(&self) {
+        if let Some(logger) = &self.context.borrow().mollusk.logger {
+            let mut logger = logger.borrow_mut();
+            logger.messages.clear();
+            logger.bytes_written = 0;
+            logger.limit_warning = false;
+        }
+    }
+
+    pub fn transaction(&self) -> TransactionBuilder<'_> {
+        TransactionBuilder::new(self)
+    }
+
+    pub fn execute_raw_instructions(
+        &self,
+        specs: &[(Pubkey, Vec<u8>, Vec<AccountMeta>)],
+    ) -> Result<TransactionResult> {
+        self.transaction()
+            .add_instructions(specs.iter().map(|(program_id, data, accounts)| {
+                Instruction::new_with_bytes(*program_id, data, accounts.clone())
+            }))
+            .execute()
+    }
+
+    pub fn create_and_initialize(
+        &self,
+        create_ix: Instruction,
+        init_ix: Instruction,
+    ) -> Result<TransactionResult> {
+        self.transaction()
+            .add_instruction(create_ix)
+            .add_instruction(init_ix)
+            .execute()
+    }
+
+    pub(crate) fn snapshot_accounts(&self) -> HashMap<Address, Account> {
+        self.context.borrow().account_store.borrow().snapshot()
+    }
+
+    pub(crate) fn restore_accounts(&self, snapshot: HashMap<Address, Account>) {
+        self.context
+            .borrow()
+            .account_store
+            .borrow_mut()
+            .restore(snapshot);
+        self.ensure_sysvar_accounts();
+    }
+
+    pub fn serialize_state(&self) -> Vec<u8> {
+        bincode::serialize(&self.accounts_sorted()).expect("Failed to serialize accounts")
+    }
+
+    pub fn deserialize_state(&self, bytes: &[u8]) -> Result<()> {
+        let accounts: Vec<(Pubkey, Account)> = bincode::deserialize(bytes)?;
+        let snapshot = accounts
+            .into_iter()
+            .map(|(pubkey, account)| (Self::pubkey_to_address(&pubkey), account))
+            .collect();
+        self.restore_accounts(snapshot);
+        Ok(())
+    }
+
+    pub fn checkpoint(&self, name: &str) -> Result<()> {
+        let snapshot = self.snapshot_accounts();
+        self.checkpoints
+            .write()
+            .map_err(|_| MolluskHelperError::LockError)?
+            .insert(name.to_string(), snapshot);
+        Ok(())
+    }
+
+    pub fn restore_checkpoint(&self, name: &str) -> Result<()> {
+        let snapshot = self.checkpoint_snapshot(name)?;
+        self.restore_accounts(snapshot);
+        Ok(())
+    }
+
+    pub fn modified_since_checkpoint(&self, name: &str) -> Result<Vec<AccountDiff>> {
+        let before = self.checkpoint_snapshot(name)?;
+        let after = self.snapshot_accounts();
+        Ok(account::diff_accounts(&before, &after))
+    }
+
+    pub fn diff_since_checkpoint(&self, name: &str) -> Result<Vec<AccountDiff>> {
+        self.modified_since_checkpoint(name)
+    }
+
+    fn checkpoint_snapshot(&self, name: &str) -> Result<HashMap<Address, Account>> {
+        self.checkpoints
+            .read()
+            .map_err(|_| MolluskHelperError::LockError)?
+            .get(name)
+            .cloned()
+            .ok_or_else(|| MolluskHelperError::CheckpointNotFound(name.to_string()))
+    }
+
+    pub fn add_account(&self, pubkey: &Pubkey, account: Account) {
+        let address = Self::pubkey_to_address(pubkey);
+        self.context
+            .borrow()
+            .account_store
+            .borrow_mut()
+            .add_account(address, account);
+    }
+
+    pub fn add_accounts(&self, entries: Vec<(Pubkey, Account)>) {
+        let context = self.context.borrow();
+        let mut store = context.account_store.borrow_mut();
+        for (pubkey, account) in entries {
+            store.add_account(Self::pubkey_to_address(&pubkey), account);
+        }
+    }
+
+    pub fn add_account_from_template(&self, pubkey: &Pubkey, template: &AccountTemplate) {
+        self.add_account(pubkey, template.build());
+    }
+
+    pub fn get_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        let address = Self::pubkey_to_address(pubkey);
+        self.context
+            .borrow()
+            .account_store
+            .borrow()
+            .get_account(&address)
+    }
+
+    pub fn remove_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        let address = Self::pubkey_to_address(pubkey);
+        self.context
+            .borrow()
+            .account_store
+            .borrow_mut()
+            .remove(&address)
+    }
+
+    pub fn account_exists(&self, pubkey: &Pubkey) -> bool {
+        self.get_account(pubkey).is_some()
+    }
+
+    pub fn add_account_from_json(&self, pubkey: &Pubkey, json: &str) -> Result<()> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| MolluskHelperError::AccountDecode(e.to_string()))?;
+        let value = value.get("account").unwrap_or(&value);
+        let account =
+            account::account_from_json(value).map_err(MolluskHelperError::AccountDecode)?;
+        self.add_account(pubkey, account);
+        Ok(())
+    }
+
+    #[cfg(feature = "rpc")]
+    pub fn clone_account_from_rpc(&self, rpc_url: &str, pubkey: &Pubkey) -> Result<()> {
+        let account = crate::rpc::get_account_info(rpc_url, pubkey)?;
+        self.add_account(pubkey, account);
+        Ok(())
+    }
+
+    pub fn get_balance(&self, pubkey: &Pubkey) -> Option<u64> {
+        let address = Self::pubkey_to_address(pubkey);
+        self.context
+            .borrow()
+            .account_store
+            .borrow()
+            .get_balance(&address)
+    }
+
+    pub fn accounts_snapshot(&self) -> HashMap<Pubkey, Account> {
+        self.context
+            .borrow()
+            .account_store
+            .borrow()
+            .accounts()
+            .map(|(address, account)| (Self::address_to_pubkey(address), account.clone()))
+            .collect()
+    }
+
+    pub fn all_accounts(&self) -> Vec<(Pubkey, Account)> {
+        self.context
+            .borrow()
+            .account_store
+            .borrow()
+            .accounts()
+            .filter(|(_, account)| !account::is_runtime_account(account))
+            .map(|(address, account)| (Self::address_to_pubkey(address), account.clone()))
+            .collect()
+    }
+
+    pub fn accounts_owned_by(&self, owner: &Pubkey) -> Vec<(Pubkey, Account)> {
+        self.context
+            .borrow()
+            .account_store
+            .borrow()
+            .accounts()
+            .filter(|(_, account)| account.owner == *owner && !account::is_runtime_account(account))
+            .map(|(address, account)| (Self::address_to_pubkey(address), account.clone()))
+            .collect()
+    }
+
+    pub fn non_rent_exempt_accounts(&self) -> Vec<(Pubkey, u64, u64)> {
+        self.context
+            .borrow()
+            .account_store
+            .borrow()
+            .accounts()
+            .filter(|(_, account)| !account::is_runtime_account(account))
+            .filter_map(|(address, account)| {
+                let required = self.minimum_balance_for_rent_exemption(account.data.len());
+                (account.lamports < required)
+                    .then(|| (Self::address_to_pubkey(address), account.lamports, required))
+            })
+            .collect()
+    }
+
+    pub fn total_account_data_bytes(&self) -> usize {
+        self.context
+            .borrow()
+            .account_store
+            .borrow()
+            .accounts()
+            .filter(|(_, account)| !account::is_runtime_account(account))
+            .map(|(_, account)| account.data.len())
+            .sum()
+    }
+
+    pub fn total_lamports(&self) -> u128 {
+        self.context
+            .borrow()
+            .account_store
+            .borrow()
+            .accounts()
+            .filter(|(_, account)| !account::is_runtime_account(account))
+            .map(|(_, account)| u128::from(account.lamports))
+            .sum()
+    }
+
+    pub fn accounts_sorted(&self) -> Vec<(Pubkey, Account)> {
+        let mut accounts: Vec<(Pubkey, Account)> = self.accounts_snapshot().into_iter().collect();
+        accounts.sort_by(|(a, _), (b, _)| a.to_bytes().cmp(&b.to_bytes()));
+        accounts
+    }
+
+    pub fn fund_account(&self, pubkey: &Pubkey, lamports: u64) {
+        let account = account::system_account_with_lamports(lamports);
+        self.add_account(pubkey, account);
+    }
+
+    pub fn fund_accounts(&self, entries: &[(Pubkey, u64)]) {
+        let context = self.context.borrow();
+        let mut store = context.account_store.borrow_mut();
+        for (pubkey, lamports) in entries {
+            store.add_account(
+                Self::pubkey_to_address(pubkey),
+                account::system_account_with_lamports(*lamports),
+            );
+        }
+    }
+
+    pub fn fund_rent_exempt(&self, pubkey: &Pubkey, data_len: usize) {
+        let required = self.minimum_balance_for_rent_exemption(data_len);
+        match self.get_account(pubkey) {
+            Some(mut account) => {
+                account.lamports = account.lamports.max(required);
+                self.add_account(pubkey, account);
+            }
+            None => self.fund_account(pubkey, required),
+        }
+    }
+
+    pub fn fund_account_exactly(&self, pubkey: &Pubkey, needed: u64) {
+        self.fund_account(pubkey, needed);
+    }
+
+    pub fn fund_account_just_below(&self, pubkey: &Pubkey, needed: u64) {
+        self.fund_account(pubkey, needed.saturating_sub(1));
+    }
+
+    pub fn minimum_balance_for_rent_exemption(&self, data_len: usize) -> u64 {
+        self.context
+            .borrow()
+            .mollusk
+            .sysvars
+            .rent
+            .minimum_balance(data_len)
+    }
+
+    pub fn create_data_accounts(&self, specs: &[(Pubkey, Pubkey, Vec<u8>)]) -> Result<()> {
+        for (pubkey, owner, data) in specs {
+            let mut account = account::program_account(owner, data.clone());
+            account.lamports = self.minimum_balance_for_rent_exemption(data.len());
+            self.add_account(pubkey, account);
+        }
+        Ok(())
+    }
+
+    pub fn close_account_manually(&self, account: &Pubkey, destination: &Pubkey) -> Result<()> {
+        if account == destination {
+            return Err(MolluskHelperError::InvalidCloseDestination(
+                destination.to_string(),
+            ));
+        }
+        let closed = self
+            .get_account(account)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(account.to_string()))?;
+        let mut receiver = self
+            .get_account(destination)
+            .unwrap_or_else(|| account::system_account_with_lamports(0));
+
+        receiver.lamports = receiver.lamports.saturating_add(closed.lamports);
+
+        self.add_account(destination, receiver);
+        self.add_account(account, account::system_account_with_lamports(0));
+        Ok(())
+    }
+
+    pub fn assert_account_closed(&self, pubkey: &Pubkey) -> Result<()> {
+        match self.get_account(pubkey) {
+            None => Ok(()),
+            Some(account) if account.lamports == 0 && account.data.iter().all(|b| *b == 0) => {
+                Ok(())
+            }
+            Some(account) => Err(MolluskHelperError::AssertionFailed(format!(
+                "account {pubkey} is not closed: {} lamports, {} bytes of data, owner {}",
+                account.lamports,
+                account.data.len(),
+                account.owner
+            ))),
+        }
+    }
+
+    pub fn assert_no_accounts_created(&self, before: &HashMap<Pubkey, Account>) -> Result<()> {
+        let mut created: Vec<Pubkey> = self
+            .accounts_snapshot()
+            .into_iter()
+            .filter(|(pubkey, account)| {
+                !before.contains_key(pubkey) && !account::is_runtime_account(account)
+            })
+            .map(|(pubkey, _)| pubkey)
+            .collect();
+        if !created.is_empty() {
+            created.sort_by_key(|pubkey| pubkey.to_bytes());
+            let created: Vec<String> = created.iter().map(ToString::to_string).collect();
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "unexpected accounts created: [{}]",
+                created.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn assert_store_empty(&self) -> Result<()> {
+        let present: Vec<String> = self
+            .accounts_sorted()
+            .into_iter()
+            .filter(|(_, account)| !account::is_runtime_account(account))
+            .map(|(pubkey, _)| pubkey.to_string())
+            .collect();
+        if !present.is_empty() {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "expected an empty store, found accounts: [{}]",
+                present.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn assert_balances(&self, expected: &[(Pubkey, u64)]) -> Result<()> {
+        let mismatches: Vec<String> = expected
+            .iter()
+            .filter_map(|(pubkey, lamports)| match self.get_balance(pubkey) {
+                Some(actual) if actual == *lamports => None,
+                Some(actual) => Some(format!("{pubkey}: expected {lamports}, got {actual}")),
+                None => Some(format!("{pubkey}: expected {lamports}, account not found")),
+            })
+            .collect();
+        if !mismatches.is_empty() {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "balance mismatches: {}",
+                mismatches.join("; ")
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn assert_account_size(&self, pubkey: &Pubkey, expected_len: usize) -> Result<()> {
+        let account = self
+            .get_account(pubkey)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(pubkey.to_string()))?;
+        if account.data.len() != expected_len {
+            return Err(MolluskHelperError::UnexpectedAccountSize {
+                expected: expected_len,
+                actual: account.data.len(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn assert_account_unchanged(
+        &self,
+        pubkey: &Pubkey,
+        before: &Account,
+        result: &InstructionResult,
+    ) -> Result<()> {
+        let after = result
+            .get_account(pubkey)
+            .cloned()
+            .or_else(|| self.get_account(pubkey))
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(pubkey.to_string()))?;
+        if after != *before {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "account {pubkey} changed: lamports {} -> {}, data {} -> {} bytes, owner {} -> {}",
+                before.lamports,
+                after.lamports,
+                before.data.len(),
+                after.data.len(),
+                before.owner,
+                after.owner
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn assert_non_trivial(&self, result: &TransactionResult) -> Result<()> {
+        if result.total_compute_units == 0 {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "transaction consumed no compute units across {} instruction(s)",
+                result.instruction_results.len()
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn assert_no_return_data(&self, result: &InstructionResult) -> Result<()> {
+        if !result.return_data.is_empty() {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "expected no return data, got {} bytes",
+                result.return_data.len()
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn assert_programs_invoked(
+        &self,
+        result: &InstructionResult,
+        expected: &[Pubkey],
+    ) -> Result<()> {
+        let message = result.message.as_ref().ok_or_else(|| {
+            MolluskHelperError::AssertionFailed("result has no compiled message".to_string())
+        })?;
+        let account_keys = message.account_keys();
+
+        let program_indices = message
+            .instructions()
+            .iter()
+            .map(|ix| ix.program_id_index)
+            .chain(
+                result
+                    .inner_instructions
+                    .iter()
+                    .map(|inner| inner.instruction.program_id_index),
+            );
+        let invoked: BTreeSet<Pubkey> = program_indices
+            .filter_map(|index| account_keys.get(index as usize))
+            .map(Self::address_to_pubkey)
+            .collect();
+        let expected: BTreeSet<Pubkey> = expected.iter().copied().collect();
+
+        if invoked != expected {
+            let unexpected: Vec<String> = invoked
+                .difference(&expected)
+                .map(ToString::to_string)
+                .collect();
+            let missing: Vec<String> = expected
+                .difference(&invoked)
+                .map(ToString::to_string)
+                .collect();
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "invoked programs differ: unexpected [{}], missing [{}]",
+                unexpected.join(", "),
+                missing.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn assert_owner_changed(
+        &self,
+        pubkey: &Pubkey,
+        before: &Account,
+        from: Pubkey,
+        to: Pubkey,
+        result: &InstructionResult,
+    ) -> Result<()> {
+        if from == to {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "expected owner of {pubkey} to change, but from and to are both {from}"
+            )));
+        }
+        if before.owner != from {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "expected owner of {pubkey} to start as {from}, but it was {}",
+                before.owner
+            )));
+        }
+        let account = result
+            .get_account(pubkey)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(pubkey.to_string()))?;
+        if account.owner != to {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "expected owner of {pubkey} to change from {from} to {to}, but it is {}",
+                account.owner
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn assert_became_rent_exempt(&self, pubkey: &Pubkey, before: &Account) -> Result<()> {
+        let after = self
+            .get_account(pubkey)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(pubkey.to_string()))?;
+        let context = self.context.borrow();
+        let rent = &context.mollusk.sysvars.rent;
+        if rent.is_exempt(before.lamports, before.data.len()) {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "account {pubkey} was already rent exempt with {} lamports",
+                before.lamports
+            )));
+        }
+        if !rent.is_exempt(after.lamports, after.data.len()) {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "account {pubkey} is not rent exempt: has {} lamports, needs {}",
+                after.lamports,
+                rent.minimum_balance(after.data.len())
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn store_keypair(&self, name: &str, keypair: Keypair) -> Result<()> {
+        self.keypairs
+            .write()
+            .map_err(|_| MolluskHelperError::LockError)?
+            .insert(name.to_string(), keypair);
+        Ok(())
+    }
+
+    pub fn create_keypair_from_seed(&self, name: &str, seed: &[u8]) -> Result<Pubkey> {
+        let keypair = solana_keypair::keypair_from_seed(seed)
+            .map_err(|e| MolluskHelperError::KeypairDerivationFailed(e.to_string()))?;
+        let pubkey = keypair.pubkey();
+        self.store_keypair(name, keypair)?;
+        Ok(pubkey)
+    }
+
+    pub fn create_user(
+        &self,
+        name: &str,
+        sol: u64,
+        mint: &Pubkey,
+        mint_authority: &Pubkey,
+        token_amount: u64,
+    ) -> Result<UserHandle> {
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        self.store_keypair(name, keypair)?;
+        self.fund_account(&pubkey, sol);
+
+        let ata = self.get_associated_token_address(&pubkey, mint);
+        let ix = token::create_associated_token_account_instruction(&pubkey, &pubkey, mint);
+        self.process_instruction(&ix)?;
+
+        if token_amount > 0 {
+            self.mint_to(mint, &ata, mint_authority, token_amount)?;
+        }
+
+        Ok(UserHandle { pubkey, ata })
+    }
+
+    pub fn sign_with(&self, name: &str, message: &[u8]) -> Result<[u8; 64]> {
+        let keypairs = self
+            .keypairs
+            .read()
+            .map_err(|_| MolluskHelperError::LockError)?;
+        let keypair = keypairs
+            .get(name)
+            .ok_or_else(|| MolluskHelperError::KeypairNotFound(name.to_string()))?;
+        Ok(keypair.sign_message(message).into())
+    }
+
+    pub fn sign_versioned_message(
+        &self,
+        message: VersionedMessage,
+        signers: &[&str],
+    ) -> Result<VersionedTransaction> {
+        let keypairs = self
+            .keypairs
+            .read()
+            .map_err(|_| MolluskHelperError::LockError)?;
+        let signing_keypairs = signers
+            .iter()
+            .map(|name| {
+                keypairs
+                    .get(*name)
+                    .map(Keypair::insecure_clone)
+                    .ok_or_else(|| MolluskHelperError::KeypairNotFound(name.to_string()))
+            })
+            .collect::<Result<Vec<Keypair>>>()?;
+        VersionedTransaction::try_new(message, &signing_keypairs)
+            .map_err(|e| MolluskHelperError::SigningFailed(e.to_string()))
+    }
+
+    pub fn assert_signed_by(&self, tx: &VersionedTransaction, name: &str) -> Result<()> {
+        let pubkey = self.get_keypair_pubkey(name)?;
+        let num_signers = usize::from(tx.message.header().num_required_signatures);
+        let index = tx
+            .message
+            .static_account_keys()
+            .iter()
+            .take(num_signers)
+            .position(|key| *key == pubkey)
+            .ok_or_else(|| {
+                MolluskHelperError::AssertionFailed(format!(
+                    "{name} ({pubkey}) is not a required signer"
+                ))
+            })?;
+        let signature = tx.signatures.get(index).ok_or_else(|| {
+            MolluskHelperError::AssertionFailed(format!("missing signature at index {index}"))
+        })?;
+        if !signature.verify(pubkey.as_ref(), &tx.message.serialize()) {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "signature at index {index} is not a valid signature by {name} ({pubkey})"
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn get_keypair_pubkey(&self, name: &str) -> Result<Pubkey> {
+        let keypairs = self
+            .keypairs
+            .read()
+            .map_err(|_| MolluskHelperError::LockError)?;
+        let keypair = keypairs
+            .get(name)
+            .ok_or_else(|| MolluskHelperError::KeypairNotFound(name.to_string()))?;
+        Ok(keypair.pubkey())
+    }
+
+    pub fn update_unix_timestamp(&mut self, timestamp: i64) {
+        if self.frozen_unix_timestamp.is_some() {
+            self.frozen_unix_timestamp = Some(timestamp);
+        }
+        self.context.get_mut().mollusk.sysvars.clock.unix_timestamp = timestamp;
+        self.ensure_sysvar_accounts();
+    }
+
+    pub fn freeze_clock(&mut self, timestamp: i64) {
+        self.frozen_unix_timestamp = Some(timestamp);
+        self.context.get_mut().mollusk.sysvars.clock.unix_timestamp = timestamp;
+        self.ensure_sysvar_accounts();
+    }
+
+    pub fn get_unix_timestamp(&self) -> i64 {
+        self.context.borrow().mollusk.sysvars.clock.unix_timestamp
+    }
+
+    pub fn warp_to_slot(&mut self, slot: u64) {
+        let mollusk = &mut self.context.get_mut().mollusk;
+        mollusk.warp_to_slot(slot);
+        if let Some(timestamp) = self.frozen_unix_timestamp {
+            mollusk.sysvars.clock.unix_timestamp = timestamp;
+        }
+        self.ensure_sysvar_accounts();
+    }
+
+    pub fn for_each_slot(
+        &mut self,
+        start: u64,
+        end: u64,
+        step: u64,
+        mut f: impl FnMut(&mut Self, u64),
+    ) {
+        for slot in (start..end).step_by(step.max(1) as usize) {
+            self.warp_to_slot(slot);
+            f(self, slot);
+        }
+    }
+
+    pub fn current_slot(&self) -> u64 {
+        self.context.borrow().mollusk.sysvars.clock.slot
+    }
+
+    pub fn current_epoch(&self) -> u64 {
+        self.context.borrow().mollusk.sysvars.clock.epoch
+    }
+
+    pub fn warp_to_epoch(&mut self, epoch: u64) {
+        let first_slot = self
+            .context
+            .borrow()
+            .mollusk
+            .sysvars
+            .epoch_schedule
+            .get_first_slot_in_epoch(epoch);
+        self.warp_to_slot(first_slot);
+    }
+
+    pub fn warp_to_next_epoch(&mut self) -> u64 {
+        let next_epoch = self.current_epoch() + 1;
+        self.warp_to_epoch(next_epoch);
+        next_epoch
+    }
+
+    pub fn set_slot_hash(&mut self, slot: u64, hash: Hash) {
+        self.context
+            .get_mut()
+            .mollusk
+            .sysvars
+            .slot_hashes
+            .add(slot, hash);
+        self.ensure_sysvar_accounts();
+    }
+
+    pub fn get_slot_hash(&self, slot: u64) -> Option<Hash> {
+        self.context
+            .borrow()
+            .mollusk
+            .sysvars
+            .slot_hashes
+            .get(&slot)
+            .copied()
+    }
+
+    pub fn set_rent(&mut self, rent: Rent) {
+        self.context.get_mut().mollusk.sysvars.rent = rent;
+        self.ensure_sysvar_accounts();
+    }
+
+    pub fn get_rent(&self) -> Rent {
+        self.context.borrow().mollusk.sysvars.rent.clone()
+    }
+
+    pub fn sysvars_summary(&self) -> SysvarsSummary {
+        SysvarsSummary::from(&self.context.borrow().mollusk.sysvars)
+    }
+
+    pub fn reset_clock(&mut self) {
+        let sysvars = &mut self.context.get_mut().mollusk.sysvars;
+        sysvars.clock = Default::default();
+        sysvars.clock.unix_timestamp = self
+            .frozen_unix_timestamp
+            .unwrap_or(self.initial_unix_timestamp);
+        sysvars.slot_hashes = Sysvars::default().slot_hashes;
+        self.ensure_sysvar_accounts();
+    }
+
+    pub fn ensure_sysvar_accounts(&self) {
+        let context = self.context.borrow();
+        let sysvars = &context.mollusk.sysvars;
+        let mut store = context.account_store.borrow_mut();
+        for (pubkey, account) in [
+            sysvars.keyed_account_for_clock_sysvar(),
+            sysvars.keyed_account_for_epoch_rewards_sysvar(),
+            sysvars.keyed_account_for_epoch_schedule_sysvar(),
+            sysvars.keyed_account_for_last_restart_slot_sysvar(),
+            sysvars.keyed_account_for_rent_sysvar(),
+            sysvars.keyed_account_for_slot_hashes_sysvar(),
+            sysvars.keyed_account_for_stake_history_sysvar(),
+        ] {
+            store.add_account(Self::pubkey_to_address(&pubkey), account);
+        }
+    }
+
+    pub fn create_mint(&self, mint_pubkey: &Pubkey, authority: &Pubkey, decimals: u8) {
+        self.create_mint_with_options(mint_pubkey, authority, decimals, None, 0);
+    }
+
+    pub fn create_mint_with_freeze_authority(
+        &self,
+        mint_pubkey: &Pubkey,
+        authority: &Pubkey,
+        freeze_authority: &Pubkey,
+        decimals: u8,
+    ) {
+        self.create_mint_with_options(mint_pubkey, authority, decimals, Some(freeze_authority), 0);
+    }
+
+    pub fn create_mint_with_options(
+        &self,
+        mint_pubkey: &Pubkey,
+        authority: &Pubkey,
+        decimals: u8,
+        freeze_authority: Option<&Pubkey>,
+        initial_supply: u64,
+    ) {
+        let mut account = token::create_mint_account_with_options(
+            authority,
+            decimals,
+            freeze_authority,
+            initial_supply,
+        );
+        account.lamports = self.minimum_balance_for_rent_exemption(account.data.len());
+        self.add_account(mint_pubkey, account);
+    }
+
+    pub fn create_token_account(
+        &self,
+        token_account_pubkey: &Pubkey,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) {
+        let mut account = token::create_token_account(mint, owner, amount);
+        account.lamports = self.minimum_balance_for_rent_exemption(account.data.len());
+        self.add_account(token_account_pubkey, account);
+    }
+
+    pub fn create_mint_2022(&self, mint_pubkey: &Pubkey, authority: &Pubkey, decimals: u8) {
+        let mut account = token::create_mint_account_2022(authority, decimals);
+        account.lamports = self.minimum_balance_for_rent_exemption(account.data.len());
+        self.add_account(mint_pubkey, account);
+    }
+
+    pub fn create_token_account_2022(
+        &self,
+        token_account_pubkey: &Pubkey,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) {
+        let mut account = token::create_token_account_2022(mint, owner, amount);
+        account.lamports = self.minimum_balance_for_rent_exemption(account.data.len());
+        self.add_account(token_account_pubkey, account);
+    }
+
+    pub fn create_associated_token_account_fixture(
+        &self,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) -> Pubkey {
+        let ata = self.get_associated_token_address(wallet, mint);
+        self.create_token_account(&ata, mint, owner, amount);
+        ata
+    }
+
+    pub fn create_token_accounts_for_mint(
+        &self,
+        mint: &Pubkey,
+        owners: &[(Pubkey, u64)],
+    ) -> Vec<Pubkey> {
+        owners
+            .iter()
+            .map(|(owner, amount)| {
+                let token_account = Pubkey::new_unique();
+                self.create_token_account(&token_account, mint, owner, *amount);
+                token_account
+            })
+            .collect()
+    }
+
+    pub fn create_native_token_account(
+        &self,
+        token_account_pubkey: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) {
+        let rent_reserve = self.minimum_balance_for_rent_exemption(TokenAccount::LEN);
+        let account = token::create_native_token_account(owner, amount, rent_reserve);
+        self.add_account(token_account_pubkey, account);
+    }
+
+    pub fn create_native_token_account_with_reserve(
+        &self,
+        token_account_pubkey: &Pubkey,
+        owner: &Pubkey,
+        lamports: u64,
+        rent_reserve: u64,
+    ) {
+        let account = token::create_native_token_account(
+            owner,
+            lamports.saturating_sub(rent_reserve),
+            rent_reserve,
         );
+        self.add_account(token_account_pubkey, account);
     }
 
-    pub fn current_unix_timestamp() -> u64 {
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_secs()
+    pub fn get_wrapped_sol_spendable(&self, token_account_pubkey: &Pubkey) -> Result<u64> {
+        let account = self
+            .get_account(token_account_pubkey)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(token_account_pubkey.to_string()))?;
+        let token_account =
+            TokenAccount::unpack(&account.data).map_err(MolluskHelperError::ProgramError)?;
+        let rent_reserve = Option::<u64>::from(token_account.is_native).ok_or_else(|| {
+            MolluskHelperError::NotNativeTokenAccount(token_account_pubkey.to_string())
+        })?;
+        Ok(account.lamports.saturating_sub(rent_reserve))
     }
 
-    pub fn process_instruction(&self, instruction: &Instruction) -> Result<InstructionResult> {
-        let result = self.context.process_instruction(instruction);
-        match &result.program_result {
-            ProgramResult::Success => Ok(result),
-            ProgramResult::Failure(e) => Err(MolluskHelperError::ProgramError(e.clone())),
-            ProgramResult::UnknownError(e) => Err(MolluskHelperError::InstructionFailed(e.clone())),
-        }
+    pub fn get_token_balance(&self, token_account_pubkey: &Pubkey) -> Result<u64> {
+        let account = self
+            .get_account(token_account_pubkey)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(token_account_pubkey.to_string()))?;
+        let token_account =
+            TokenAccount::unpack(&account.data).map_err(MolluskHelperError::ProgramError)?;
+        Ok(token_account.amount)
     }
 
-    pub fn process_instruction_unchecked(&self, instruction: &Instruction) -> InstructionResult {
-        self.context.process_instruction(instruction)
+    pub fn get_token_account_info(
+        &self,
+        token_account_pubkey: &Pubkey,
+    ) -> Result<TokenAccountInfo> {
+        let account = self
+            .get_account(token_account_pubkey)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(token_account_pubkey.to_string()))?;
+        let token_account =
+            TokenAccount::unpack(&account.data).map_err(MolluskHelperError::ProgramError)?;
+        Ok(token_account.into())
     }
 
-    pub(crate) fn process_instruction_internal(
-        &self,
-        instruction: &Instruction,
-    ) -> InstructionResult {
-        self.context.process_instruction(instruction)
+    pub fn get_mint_info(&self, mint_pubkey: &Pubkey) -> Result<MintInfo> {
+        let account = self
+            .get_account(mint_pubkey)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(mint_pubkey.to_string()))?;
+        let mint = Mint::unpack(&account.data).map_err(MolluskHelperError::ProgramError)?;
+        Ok(mint.into())
     }
 
-    pub fn transaction(&self) -> TransactionBuilder<'_> {
-        TransactionBuilder::new(self)
+    pub fn snapshot_token_accounts(&self) -> HashMap<Pubkey, TokenAccountInfo> {
+        self.context
+            .borrow()
+            .account_store
+            .borrow()
+            .accounts()
+            .filter(|(_, account)| {
+                account.owner == token::TOKEN_PROGRAM_ID && account.data.len() == TokenAccount::LEN
+            })
+            .filter_map(|(address, account)| {
+                let token_account = TokenAccount::unpack(&account.data).ok()?;
+                Some((Self::address_to_pubkey(address), token_account.into()))
+            })
+            .collect()
     }
 
-    pub(crate) fn snapshot_accounts(&self) -> HashMap<Address, Account> {
-        self.context.account_store.borrow().snapshot()
+    pub fn restore_token_accounts(
+        &self,
+        snapshot: &HashMap<Pubkey, TokenAccountInfo>,
+    ) -> Result<()> {
+        for (pubkey, info) in snapshot {
+            let mut account = self
+                .get_account(pubkey)
+                .filter(|account| {
+                    account.owner == token::TOKEN_PROGRAM_ID
+                        && account.data.len() == TokenAccount::LEN
+                })
+                .unwrap_or_else(|| token::create_token_account(&info.mint, &info.owner, 0));
+            TokenAccount::pack(info.into(), &mut account.data)
+                .map_err(MolluskHelperError::ProgramError)?;
+            self.add_account(pubkey, account);
+        }
+        Ok(())
     }
 
-    pub(crate) fn restore_accounts(&self, snapshot: HashMap<Address, Account>) {
-        self.context.account_store.borrow_mut().restore(snapshot);
+    pub fn get_freeze_authority(&self, mint_pubkey: &Pubkey) -> Result<Option<Pubkey>> {
+        let account = self
+            .get_account(mint_pubkey)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(mint_pubkey.to_string()))?;
+        let mint = Mint::unpack(&account.data).map_err(MolluskHelperError::ProgramError)?;
+        Ok(mint.freeze_authority.into())
     }
 
-    pub fn add_account(&self, pubkey: &Pubkey, account: Account) {
-        let address = Self::pubkey_to_address(pubkey);
-        self.context
+    pub fn assert_supply_conserved(&self, mint_pubkey: &Pubkey) -> Result<()> {
+        let account = self
+            .get_account(mint_pubkey)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(mint_pubkey.to_string()))?;
+        let mint = Mint::unpack(&account.data).map_err(MolluskHelperError::ProgramError)?;
+
+        let total: u128 = self
+            .context
+            .borrow()
             .account_store
-            .borrow_mut()
-            .add_account(address, account);
+            .borrow()
+            .accounts()
+            .filter(|(_, a)| {
+                a.owner == token::TOKEN_PROGRAM_ID && a.data.len() == TokenAccount::LEN
+            })
+            .filter_map(|(_, a)| TokenAccount::unpack(&a.data).ok())
+            .filter(|t| t.mint == *mint_pubkey)
+            .map(|t| t.amount as u128)
+            .sum();
+
+        if total != mint.supply as u128 {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "supply of mint {mint_pubkey} is {} but token accounts hold {total}",
+                mint.supply
+            )));
+        }
+        Ok(())
     }
 
-    pub fn get_account(&self, pubkey: &Pubkey) -> Option<Account> {
-        let address = Self::pubkey_to_address(pubkey);
-        self.context.account_store.borrow().get_account(&address)
+    pub fn initialize_mint2(
+        &self,
+        mint_pubkey: &Pubkey,
+        authority: &Pubkey,
+        freeze_authority: Option<&Pubkey>,
+        decimals: u8,
+    ) -> Result<InstructionResult> {
+        let ix =
+            token::initialize_mint2_instruction(mint_pubkey, authority, freeze_authority, decimals);
+        self.process_instruction(&ix)
     }
 
-    pub fn get_balance(&self, pubkey: &Pubkey) -> Option<u64> {
-        let address = Self::pubkey_to_address(pubkey);
-        self.context.account_store.borrow().get_balance(&address)
+    pub fn mint_to(
+        &self,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+    ) -> Result<InstructionResult> {
+        self.mint_to_with_program(mint, destination, authority, amount, TokenProgram::Legacy)
     }
 
-    pub fn fund_account(&self, pubkey: &Pubkey, lamports: u64) {
-        let account = account::system_account_with_lamports(lamports);
-        self.add_account(pubkey, account);
+    pub fn mint_to_with_program(
+        &self,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+        program: TokenProgram,
+    ) -> Result<InstructionResult> {
+        let ix = token::mint_to_instruction(mint, destination, authority, amount, program);
+        self.process_instruction(&ix)
     }
 
-    pub fn store_keypair(&self, name: &str, keypair: Keypair) -> Result<()> {
-        self.keypairs
-            .write()
-            .map_err(|_| MolluskHelperError::LockError)?
-            .insert(name.to_string(), keypair);
-        Ok(())
+    pub fn burn_tokens(
+        &self,
+        mint: &Pubkey,
+        account: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+    ) -> Result<InstructionResult> {
+        let ix = token::burn_instruction(mint, account, authority, amount);
+        self.process_instruction(&ix)
     }
 
-    pub fn sign_with(&self, name: &str, message: &[u8]) -> Result<[u8; 64]> {
-        let keypairs = self
-            .keypairs
-            .read()
-            .map_err(|_| MolluskHelperError::LockError)?;
-        let keypair = keypairs
-            .get(name)
-            .ok_or_else(|| MolluskHelperError::KeypairNotFound(name.to_string()))?;
-        Ok(keypair.sign_message(message).into())
+    pub fn freeze_token_account(
+        &self,
+        account: &Pubkey,
+        mint: &Pubkey,
+        freeze_authority: &Pubkey,
+    ) -> Result<InstructionResult> {
+        let ix = token::freeze_account_instruction(account, mint, freeze_authority);
+        self.process_instruction(&ix)
     }
 
-    pub fn get_keypair_pubkey(&self, name: &str) -> Result<Pubkey> {
-        let keypairs = self
-            .keypairs
-            .read()
-            .map_err(|_| MolluskHelperError::LockError)?;
-        let keypair = keypairs
-            .get(name)
-            .ok_or_else(|| MolluskHelperError::KeypairNotFound(name.to_string()))?;
-        Ok(keypair.pubkey())
+    pub fn thaw_token_account(
+        &self,
+        account: &Pubkey,
+        mint: &Pubkey,
+        freeze_authority: &Pubkey,
+    ) -> Result<InstructionResult> {
+        let ix = token::thaw_account_instruction(account, mint, freeze_authority);
+        self.process_instruction(&ix)
     }
 
-    pub fn update_unix_timestamp(&mut self, timestamp: i64) {
-        self.context.mollusk.sysvars.clock.unix_timestamp = timestamp;
+    pub fn transfer_tokens(
+        &self,
+        source: &Pubkey,
+        destination: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+    ) -> Result<InstructionResult> {
+        self.transfer_tokens_with_program(
+            source,
+            destination,
+            authority,
+            amount,
+            TokenProgram::Legacy,
+        )
     }
 
-    pub fn get_unix_timestamp(&self) -> i64 {
-        self.context.mollusk.sysvars.clock.unix_timestamp
+    pub fn transfer_tokens_with_program(
+        &self,
+        source: &Pubkey,
+        destination: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+        program: TokenProgram,
+    ) -> Result<InstructionResult> {
+        let ix = token::transfer_instruction(source, destination, authority, amount, program);
+        self.process_instruction(&ix)
     }
 
-    pub fn warp_to_slot(&mut self, slot: u64) {
-        self.context.mollusk.warp_to_slot(slot);
+    pub fn approve_delegate(
+        &self,
+        source: &Pubkey,
+        delegate: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+    ) -> Result<InstructionResult> {
+        let ix = token::approve_instruction(source, delegate, authority, amount);
+        self.process_instruction(&ix)
     }
 
-    pub fn create_mint(&self, mint_pubkey: &Pubkey, authority: &Pubkey, decimals: u8) {
-        let account = token::create_mint_account(authority, decimals);
-        self.add_account(mint_pubkey, account);
+    pub fn revoke_delegate(
+        &self,
+        source: &Pubkey,
+        authority: &Pubkey,
+    ) -> Result<InstructionResult> {
+        let ix = token::revoke_instruction(source, authority);
+        self.process_instruction(&ix)
     }
 
-    pub fn create_token_account(
+    pub fn mint_to_2022(
         &self,
-        token_account_pubkey: &Pubkey,
         mint: &Pubkey,
-        owner: &Pubkey,
+        destination: &Pubkey,
+        authority: &Pubkey,
         amount: u64,
-    ) {
-        let account = token::create_token_account(mint, owner, amount);
-        self.add_account(token_account_pubkey, account);
+    ) -> Result<InstructionResult> {
+        self.mint_to_with_program(
+            mint,
+            destination,
+            authority,
+            amount,
+            TokenProgram::Token2022,
+        )
     }
 
-    pub fn create_native_token_account(
+    pub fn transfer_tokens_2022(
         &self,
-        token_account_pubkey: &Pubkey,
-        owner: &Pubkey,
-        lamports: u64,
-    ) {
-        let account = token::create_native_token_account(owner, lamports);
-        self.add_account(token_account_pubkey, account);
+        source: &Pubkey,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+        decimals: u8,
+    ) -> Result<InstructionResult> {
+        let ix = token::transfer_checked_2022_instruction(
+            source,
+            mint,
+            destination,
+            authority,
+            amount,
+            decimals,
+        );
+        self.process_instruction(&ix)
     }
 
-    pub fn get_token_balance(&self, token_account_pubkey: &Pubkey) -> Result<u64> {
-        let account = self
-            .get_account(token_account_pubkey)
-            .ok_or_else(|| MolluskHelperError::AccountNotFound(token_account_pubkey.to_string()))?;
-        let token_account =
-            TokenAccount::unpack(&account.data).map_err(MolluskHelperError::ProgramError)?;
-        Ok(token_account.amount)
+    pub fn atomic_swap(
+        &self,
+        (src_x, dst_x, auth_x, amt_x): (&Pubkey, &Pubkey, &Pubkey, u64),
+        (src_y, dst_y, auth_y, amt_y): (&Pubkey, &Pubkey, &Pubkey, u64),
+    ) -> Result<TransactionResult> {
+        self.transaction()
+            .add_instruction(token::transfer_instruction(
+                src_x,
+                dst_x,
+                auth_x,
+                amt_x,
+                TokenProgram::Legacy,
+            ))
+            .add_instruction(token::transfer_instruction(
+                src_y,
+                dst_y,
+                auth_y,
+                amt_y,
+                TokenProgram::Legacy,
+            ))
+            .execute()
     }
 
-    pub fn mint_to(
+    pub fn burn_checked(
         &self,
         mint: &Pubkey,
-        destination: &Pubkey,
+        account: &Pubkey,
         authority: &Pubkey,
         amount: u64,
+        decimals: u8,
     ) -> Result<InstructionResult> {
-        let ix = token::mint_to_instruction(mint, destination, authority, amount);
+        let ix = token::burn_checked_instruction(mint, account, authority, amount, decimals);
         self.process_instruction(&ix)
     }
 
-    pub fn transfer_tokens(
+    pub fn close_token_account(
         &self,
-        source: &Pubkey,
+        account: &Pubkey,
         destination: &Pubkey,
         authority: &Pubkey,
-        amount: u64,
-    ) -> Result<InstructionResult> {
-        let ix = token::transfer_instruction(source, destination, authority, amount);
-        self.process_instruction(&ix)
+    ) -> Result<(InstructionResult, u64)> {
+        let before = self.get_balance(destination).unwrap_or(0);
+        let ix = token::close_account_instruction(account, destination, authority);
+        let result = self.process_instruction(&ix)?;
+        let after = self.get_balance(destination).unwrap_or(0);
+        Ok((result, after.saturating_sub(before)))
     }
 
     pub fn sync_native(&self, token_account: &Pubkey) -> Result<InstructionResult> {
@@ -324,10 +1848,76 @@ impl MolluskContextHelper {
         self.process_instruction(&ix)
     }
 
+    pub fn transfer_sol_checked(
+        &self,
+        from: &Pubkey,
+        to: &Pubkey,
+        lamports: u64,
+    ) -> Result<InstructionResult> {
+        let account = self
+            .get_account(from)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(from.to_string()))?;
+        let reserve = if account.data.is_empty() {
+            0
+        } else {
+            self.minimum_balance_for_rent_exemption(account.data.len())
+        };
+        let need = lamports.saturating_add(reserve);
+        if account.lamports < need {
+            return Err(MolluskHelperError::InsufficientFunds {
+                have: account.lamports,
+                need,
+            });
+        }
+        self.transfer_sol(from, to, lamports)
+    }
+
+    pub fn transfer_sol_with_balances(
+        &self,
+        from: &Pubkey,
+        to: &Pubkey,
+        lamports: u64,
+    ) -> Result<(u64, u64)> {
+        self.transfer_sol(from, to, lamports)?;
+        let from_balance = self
+            .get_balance(from)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(from.to_string()))?;
+        let to_balance = self
+            .get_balance(to)
+            .ok_or_else(|| MolluskHelperError::AccountNotFound(to.to_string()))?;
+        Ok((from_balance, to_balance))
+    }
+
+    pub fn find_pda(&self, seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(seeds, program_id)
+    }
+
+    pub fn create_pda_address(
+        &self,
+        seeds: &[&[u8]],
+        bump: u8,
+        program_id: &Pubkey,
+    ) -> Result<Pubkey> {
+        let bump = [bump];
+        let mut seeds_with_bump = seeds.to_vec();
+        seeds_with_bump.push(&bump);
+        Pubkey::create_program_address(&seeds_with_bump, program_id)
+            .map_err(|e| MolluskHelperError::InvalidSeeds(e.to_string()))
+    }
+
     pub fn get_associated_token_address(&self, wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
         token::get_associated_token_address(wallet, mint)
     }
 
+    pub fn get_associated_token_address_for_program(
+        &self,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Pubkey {
+        token::get_associated_token_address_for_program(wallet, mint, token_program)
+    }
+
     pub fn create_lookup_table_account(
         key: Pubkey,
         addresses: Vec<Pubkey>,
@@ -364,6 +1954,108 @@ impl MolluskContextHelper {
         (tx_size, tx_limit, remaining)
     }
 
+    pub fn execute_versioned(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<TransactionResult> {
+        let message = v0::Message::try_compile(payer, instructions, lookup_tables, Hash::default())
+            .map_err(|e| MolluskHelperError::InvalidMessage(e.to_string()))?;
+
+        let mut writable = Vec::new();
+        let mut readonly = Vec::new();
+        for lookup in &message.address_table_lookups {
+            let table = lookup_tables
+                .iter()
+                .find(|table| table.key == lookup.account_key)
+                .ok_or_else(|| {
+                    MolluskHelperError::AccountNotFound(lookup.account_key.to_string())
+                })?;
+            for (indexes, resolved) in [
+                (&lookup.writable_indexes, &mut writable),
+                (&lookup.readonly_indexes, &mut readonly),
+            ] {
+                for index in indexes {
+                    let address = table.addresses.get(usize::from(*index)).ok_or_else(|| {
+                        MolluskHelperError::InvalidMessage(format!(
+                            "lookup index {index} out of range for table {}",
+                            table.key
+                        ))
+                    })?;
+                    resolved.push(*address);
+                }
+            }
+        }
+
+        let account_keys: Vec<Pubkey> = message
+            .account_keys
+            .iter()
+            .chain(&writable)
+            .chain(&readonly)
+            .copied()
+            .collect();
+        let num_signers = usize::from(message.header.num_required_signatures);
+        let account_key = |index: usize| {
+            account_keys.get(index).copied().ok_or_else(|| {
+                MolluskHelperError::InvalidMessage(format!(
+                    "account index {index} out of range for {} account keys",
+                    account_keys.len()
+                ))
+            })
+        };
+
+        let mut resolved = Vec::with_capacity(message.instructions.len());
+        for ix in &message.instructions {
+            let mut accounts = Vec::with_capacity(ix.accounts.len());
+            for index in &ix.accounts {
+                let index = usize::from(*index);
+                accounts.push(AccountMeta {
+                    pubkey: account_key(index)?,
+                    is_signer: index < num_signers,
+                    is_writable: message.is_maybe_writable(index, None),
+                });
+            }
+            resolved.push(Instruction {
+                program_id: account_key(usize::from(ix.program_id_index))?,
+                accounts,
+                data: ix.data.clone(),
+            });
+        }
+
+        self.transaction().add_instructions(resolved).execute()
+    }
+
+    pub fn assert_tx_fits(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<()> {
+        let (size, limit, remaining) =
+            Self::build_versioned_tx_size(payer, instructions, lookup_tables);
+        if remaining < 0 {
+            return Err(MolluskHelperError::TransactionTooLarge { size, limit });
+        }
+        Ok(())
+    }
+
+    pub fn build_max_size_transfer_chain(payer: &Pubkey, count: usize) -> Vec<Instruction> {
+        let mut instructions = Vec::new();
+        for _ in 0..count {
+            instructions.push(solana_system_interface::instruction::transfer(
+                payer,
+                &Pubkey::new_unique(),
+                1,
+            ));
+            let (_, _, remaining) = Self::build_versioned_tx_size(payer, &instructions, &[]);
+            if remaining < 0 {
+                instructions.pop();
+                break;
+            }
+        }
+        instructions
+    }
+
     pub fn pubkey_to_address(pubkey: &Pubkey) -> Address {
         Address::new_from_array(pubkey.to_bytes())
     }
@@ -372,6 +2064,23 @@ impl MolluskContextHelper {
         Pubkey::from(address.to_bytes())
     }
 
+    pub fn pubkeys_to_addresses(pubkeys: &[Pubkey]) -> Vec<Address> {
+        pubkeys.iter().map(Self::pubkey_to_address).collect()
+    }
+
+    pub fn addresses_to_pubkeys(addresses: &[Address]) -> Vec<Pubkey> {
+        addresses.iter().map(Self::address_to_pubkey).collect()
+    }
+
+    pub fn assert_pubkey_address_consistent(pubkey: &Pubkey, address: &Address) -> Result<()> {
+        if Self::pubkey_to_address(pubkey) != *address {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "pubkey {pubkey} does not map to address {address}"
+            )));
+        }
+        Ok(())
+    }
+
     pub fn system_program() -> Pubkey {
         solana_pubkey::pubkey!("11111111111111111111111111111111")
     }
@@ -417,6 +2126,16 @@ impl MolluskContextHelper {
         self.add_account(pubkey, account);
     }
 
+    pub fn create_executable_account(&self, pubkey: &Pubkey, loader: &Pubkey, data: Vec<u8>) {
+        let account = account::executable_account(loader, data);
+        self.add_account(pubkey, account);
+    }
+
+    pub fn is_account_executable(&self, pubkey: &Pubkey) -> bool {
+        self.get_account(pubkey)
+            .is_some_and(|account| account.executable)
+    }
+
     pub fn create_associated_token_account_instruction(
         payer: &Pubkey,
         wallet: &Pubkey,
@@ -10,9 +10,10 @@ pub enum MolluskHelperError {
     #[error("Program error: {0}")]
     ProgramError(#[from] ProgramError),
 
-    #[error("Transaction failed at instruction {index}: {error}")]
+    #[error("Transaction failed at instruction {index} ({label}): {error}")]
     TransactionFailed {
         index: usize,
+        label: String,
         error: InstructionError,
     },
 
@@ -22,8 +23,65 @@ pub enum MolluskHelperError {
     #[error("Keypair not found: {0}")]
     KeypairNotFound(String),
 
+    #[error("Cannot close an account into itself: {0}")]
+    InvalidCloseDestination(String),
+
+    #[error("Keypair derivation failed: {0}")]
+    KeypairDerivationFailed(String),
+
+    #[error("Signing failed: {0}")]
+    SigningFailed(String),
+
+    #[error("Program not loaded: {0}")]
+    ProgramNotLoaded(String),
+
+    #[error("CPI depth exceeded: limit is {0}")]
+    CpiDepthExceeded(usize),
+
+    #[error("RPC error: {0}")]
+    RpcError(String),
+
+    #[error("Account decode failed: {0}")]
+    AccountDecode(String),
+
+    #[error("Invalid PDA seeds: {0}")]
+    InvalidSeeds(String),
+
+    #[error("Insufficient funds: have {have} lamports, need {need}")]
+    InsufficientFunds { have: u64, need: u64 },
+
+    #[error("Not a native token account: {0}")]
+    NotNativeTokenAccount(String),
+
+    #[error("Invalid token setup: {0}")]
+    InvalidTokenSetup(String),
+
+    #[error("Checkpoint not found: {0}")]
+    CheckpointNotFound(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] bincode::Error),
+
     #[error("Lock acquisition failed")]
     LockError,
+
+    #[error("Assertion failed: {0}")]
+    AssertionFailed(String),
+
+    #[error("Log assertion failed: {0}")]
+    LogAssertionFailed(String),
+
+    #[error("Unexpected account size: expected {expected} bytes, got {actual}")]
+    UnexpectedAccountSize { expected: usize, actual: usize },
+
+    #[error("Invalid message: {0}")]
+    InvalidMessage(String),
+
+    #[error("Transaction too large: {size} bytes exceeds the {limit} byte limit")]
+    TransactionTooLarge { size: usize, limit: usize },
 }
 
 pub type Result<T> = std::result::Result<T, MolluskHelperError>;
@@ -2,28 +2,47 @@ mod account;
 mod account_store;
 mod context;
 mod error;
+mod program_cache;
+#[cfg(feature = "rpc")]
+mod rpc;
+mod sysvars;
 mod token;
 mod transaction;
 
+pub use account::{AccountDiff, AccountTemplate};
 pub use context::{
-    MolluskContextHelper, ProgramLoader, ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
-    COMPUTE_BUDGET_PROGRAM_ID, MEMO_PROGRAM_ID, MEMO_V1_PROGRAM_ID, TOKEN_2022_PROGRAM_ID,
+    MolluskContextHelper, ProgramLoader, UserHandle, ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+    COMPUTE_BUDGET_PROGRAM_ID, DEFAULT_LAMPORTS_PER_SIGNATURE, MEMO_PROGRAM_ID, MEMO_V1_PROGRAM_ID,
+    TOKEN_2022_PROGRAM_ID,
 };
 pub use error::{MolluskHelperError, Result};
+pub use program_cache::ProgramCache;
+pub use sysvars::SysvarsSummary;
+pub use token::{MintInfo, TokenAccountInfo, TokenAccountState, TokenProgram, TokenSetup};
 pub use transaction::{TransactionBuilder, TransactionResult};
 
 pub mod prelude {
+    pub use crate::account::{AccountDiff, AccountTemplate};
     pub use crate::context::{
-        MolluskContextHelper, ProgramLoader, ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
-        COMPUTE_BUDGET_PROGRAM_ID, MEMO_PROGRAM_ID, MEMO_V1_PROGRAM_ID, TOKEN_2022_PROGRAM_ID,
+        MolluskContextHelper, ProgramLoader, UserHandle, ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+        COMPUTE_BUDGET_PROGRAM_ID, DEFAULT_LAMPORTS_PER_SIGNATURE, MEMO_PROGRAM_ID,
+        MEMO_V1_PROGRAM_ID, TOKEN_2022_PROGRAM_ID,
     };
     pub use crate::error::{MolluskHelperError, Result};
+    pub use crate::program_cache::ProgramCache;
+    pub use crate::sysvars::SysvarsSummary;
+    pub use crate::token::{
+        MintInfo, TokenAccountInfo, TokenAccountState, TokenProgram, TokenSetup,
+    };
     pub use crate::transaction::{TransactionBuilder, TransactionResult};
 
     pub use mollusk_svm::result::{Check, InstructionResult, ProgramResult};
     pub use solana_account::Account;
     pub use solana_instruction::{AccountMeta, Instruction};
     pub use solana_keypair::Keypair;
+    pub use solana_message::VersionedMessage;
     pub use solana_pubkey::Pubkey;
+    pub use solana_rent::Rent;
     pub use solana_signer::Signer;
+    pub use solana_transaction::versioned::VersionedTransaction;
 }
@@ -0,0 +1,51 @@
+use crate::context::ProgramLoader;
+use crate::error::Result;
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default)]
+pub struct ProgramCache {
+    programs: HashMap<Pubkey, (ProgramLoader, Arc<[u8]>)>,
+}
+
+impl ProgramCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_program(&mut self, program_id: &Pubkey, elf_bytes: &[u8]) {
+        self.add_program_with_loader(program_id, elf_bytes, ProgramLoader::V3);
+    }
+
+    pub fn add_program_with_loader(
+        &mut self,
+        program_id: &Pubkey,
+        elf_bytes: &[u8],
+        loader: ProgramLoader,
+    ) {
+        self.programs
+            .insert(*program_id, (loader, Arc::from(elf_bytes)));
+    }
+
+    pub fn add_program_from_file(
+        &mut self,
+        program_id: &Pubkey,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let elf_bytes = std::fs::read(path)?;
+        self.add_program(program_id, &elf_bytes);
+        Ok(())
+    }
+
+    pub fn contains(&self, program_id: &Pubkey) -> bool {
+        self.programs.contains_key(program_id)
+    }
+
+    pub(crate) fn get(&self, program_id: &Pubkey) -> Option<(ProgramLoader, Arc<[u8]>)> {
+        self.programs
+            .get(program_id)
+            .map(|(loader, elf_bytes)| (*loader, Arc::clone(elf_bytes)))
+    }
+}
@@ -0,0 +1,30 @@
+use crate::account;
+use crate::error::{MolluskHelperError, Result};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+
+pub(crate) fn get_account_info(rpc_url: &str, pubkey: &Pubkey) -> Result<Account> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getAccountInfo",
+        "params": [pubkey.to_string(), { "encoding": "base64" }],
+    });
+
+    let response: serde_json::Value = ureq::post(rpc_url)
+        .send_json(request)
+        .map_err(|e| MolluskHelperError::RpcError(e.to_string()))?
+        .into_json()
+        .map_err(|e| MolluskHelperError::RpcError(e.to_string()))?;
+
+    if let Some(error) = response.get("error") {
+        return Err(MolluskHelperError::RpcError(error.to_string()));
+    }
+
+    let value = &response["result"]["value"];
+    if value.is_null() {
+        return Err(MolluskHelperError::AccountNotFound(pubkey.to_string()));
+    }
+
+    account::account_from_json(value).map_err(MolluskHelperError::RpcError)
+}
@@ -0,0 +1,44 @@
+use mollusk_svm::sysvar::Sysvars;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SysvarsSummary {
+    pub slot: u64,
+    pub epoch: u64,
+    pub epoch_start_timestamp: i64,
+    pub leader_schedule_epoch: u64,
+    pub unix_timestamp: i64,
+    pub rent_exempt_base_lamports: u64,
+    pub rent_exempt_lamports_per_byte: u64,
+    pub slots_per_epoch: u64,
+    pub leader_schedule_slot_offset: u64,
+    pub warmup: bool,
+    pub first_normal_epoch: u64,
+    pub first_normal_slot: u64,
+}
+
+impl From<&Sysvars> for SysvarsSummary {
+    fn from(sysvars: &Sysvars) -> Self {
+        let clock = &sysvars.clock;
+        let rent = &sysvars.rent;
+        let epoch_schedule = &sysvars.epoch_schedule;
+        let rent_exempt_base_lamports = rent.minimum_balance(0);
+
+        Self {
+            slot: clock.slot,
+            epoch: clock.epoch,
+            epoch_start_timestamp: clock.epoch_start_timestamp,
+            leader_schedule_epoch: clock.leader_schedule_epoch,
+            unix_timestamp: clock.unix_timestamp,
+            rent_exempt_base_lamports,
+            rent_exempt_lamports_per_byte: rent
+                .minimum_balance(1)
+                .saturating_sub(rent_exempt_base_lamports),
+            slots_per_epoch: epoch_schedule.slots_per_epoch,
+            leader_schedule_slot_offset: epoch_schedule.leader_schedule_slot_offset,
+            warmup: epoch_schedule.warmup,
+            first_normal_epoch: epoch_schedule.first_normal_epoch,
+            first_normal_slot: epoch_schedule.first_normal_slot,
+        }
+    }
+}
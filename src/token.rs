@@ -1,3 +1,5 @@
+use crate::context::{MolluskContextHelper, TOKEN_2022_PROGRAM_ID};
+use crate::error::{MolluskHelperError, Result};
 use solana_account::Account;
 use solana_instruction::Instruction;
 use solana_program_pack::Pack;
@@ -13,13 +15,120 @@ pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
 pub const NATIVE_MINT: Pubkey =
     solana_pubkey::pubkey!("So11111111111111111111111111111111111111112");
 
-pub fn create_mint_account(mint_authority: &Pubkey, decimals: u8) -> Account {
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TokenProgram {
+    #[default]
+    Legacy,
+    Token2022,
+}
+
+impl TokenProgram {
+    pub fn id(&self) -> Pubkey {
+        match self {
+            TokenProgram::Legacy => TOKEN_PROGRAM_ID,
+            TokenProgram::Token2022 => TOKEN_2022_PROGRAM_ID,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenAccountState {
+    Uninitialized,
+    Initialized,
+    Frozen,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenAccountInfo {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub delegate: Option<Pubkey>,
+    pub state: TokenAccountState,
+    pub is_native: Option<u64>,
+    pub delegated_amount: u64,
+    pub close_authority: Option<Pubkey>,
+}
+
+impl From<TokenAccount> for TokenAccountInfo {
+    fn from(account: TokenAccount) -> Self {
+        Self {
+            mint: account.mint,
+            owner: account.owner,
+            amount: account.amount,
+            delegate: account.delegate.into(),
+            state: match account.state {
+                AccountState::Uninitialized => TokenAccountState::Uninitialized,
+                AccountState::Initialized => TokenAccountState::Initialized,
+                AccountState::Frozen => TokenAccountState::Frozen,
+            },
+            is_native: account.is_native.into(),
+            delegated_amount: account.delegated_amount,
+            close_authority: account.close_authority.into(),
+        }
+    }
+}
+
+impl From<&TokenAccountInfo> for TokenAccount {
+    fn from(info: &TokenAccountInfo) -> Self {
+        Self {
+            mint: info.mint,
+            owner: info.owner,
+            amount: info.amount,
+            delegate: info.delegate.into(),
+            state: match info.state {
+                TokenAccountState::Uninitialized => AccountState::Uninitialized,
+                TokenAccountState::Initialized => AccountState::Initialized,
+                TokenAccountState::Frozen => AccountState::Frozen,
+            },
+            is_native: info.is_native.into(),
+            delegated_amount: info.delegated_amount,
+            close_authority: info.close_authority.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MintInfo {
+    pub mint_authority: Option<Pubkey>,
+    pub supply: u64,
+    pub decimals: u8,
+    pub is_initialized: bool,
+    pub freeze_authority: Option<Pubkey>,
+}
+
+impl From<Mint> for MintInfo {
+    fn from(mint: Mint) -> Self {
+        Self {
+            mint_authority: mint.mint_authority.into(),
+            supply: mint.supply,
+            decimals: mint.decimals,
+            is_initialized: mint.is_initialized,
+            freeze_authority: mint.freeze_authority.into(),
+        }
+    }
+}
+
+pub fn create_mint_account(
+    mint_authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
+    decimals: u8,
+) -> Account {
+    create_mint_account_with_options(mint_authority, decimals, freeze_authority, 0)
+}
+
+pub fn create_mint_account_with_options(
+    mint_authority: &Pubkey,
+    decimals: u8,
+    freeze_authority: Option<&Pubkey>,
+    initial_supply: u64,
+) -> Account {
     let mint = Mint {
         mint_authority: solana_program_option::COption::Some(*mint_authority),
-        supply: 0,
+        supply: initial_supply,
         decimals,
         is_initialized: true,
-        freeze_authority: solana_program_option::COption::None,
+        freeze_authority: freeze_authority.copied().into(),
     };
 
     let mut data = vec![0u8; Mint::LEN];
@@ -58,14 +167,14 @@ pub fn create_token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Accou
     }
 }
 
-pub fn create_native_token_account(owner: &Pubkey, lamports: u64) -> Account {
+pub fn create_native_token_account(owner: &Pubkey, amount: u64, rent_reserve: u64) -> Account {
     let token_account = TokenAccount {
         mint: NATIVE_MINT,
         owner: *owner,
-        amount: lamports,
+        amount,
         delegate: solana_program_option::COption::None,
         state: AccountState::Initialized,
-        is_native: solana_program_option::COption::Some(0),
+        is_native: solana_program_option::COption::Some(rent_reserve),
         delegated_amount: 0,
         close_authority: solana_program_option::COption::None,
     };
@@ -74,7 +183,7 @@ pub fn create_native_token_account(owner: &Pubkey, lamports: u64) -> Account {
     TokenAccount::pack(token_account, &mut data).unwrap();
 
     Account {
-        lamports,
+        lamports: amount.saturating_add(rent_reserve),
         data,
         owner: TOKEN_PROGRAM_ID,
         executable: false,
@@ -82,33 +191,214 @@ pub fn create_native_token_account(owner: &Pubkey, lamports: u64) -> Account {
     }
 }
 
+pub fn create_mint_account_2022(mint_authority: &Pubkey, decimals: u8) -> Account {
+    let mint = spl_token_2022_interface::state::Mint {
+        mint_authority: solana_program_option::COption::Some(*mint_authority),
+        supply: 0,
+        decimals,
+        is_initialized: true,
+        freeze_authority: solana_program_option::COption::None,
+    };
+
+    let mut data = vec![0u8; spl_token_2022_interface::state::Mint::LEN];
+    spl_token_2022_interface::state::Mint::pack(mint, &mut data).unwrap();
+
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: TOKEN_2022_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn create_token_account_2022(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let token_account = spl_token_2022_interface::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        delegate: solana_program_option::COption::None,
+        state: spl_token_2022_interface::state::AccountState::Initialized,
+        is_native: solana_program_option::COption::None,
+        delegated_amount: 0,
+        close_authority: solana_program_option::COption::None,
+    };
+
+    let mut data = vec![0u8; spl_token_2022_interface::state::Account::LEN];
+    spl_token_2022_interface::state::Account::pack(token_account, &mut data).unwrap();
+
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: TOKEN_2022_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn initialize_mint2_instruction(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
+    decimals: u8,
+) -> Instruction {
+    spl_token::instruction::initialize_mint2(
+        &TOKEN_PROGRAM_ID,
+        mint,
+        authority,
+        freeze_authority,
+        decimals,
+    )
+    .unwrap()
+}
+
 pub fn mint_to_instruction(
     mint: &Pubkey,
     destination: &Pubkey,
     authority: &Pubkey,
     amount: u64,
+    program: TokenProgram,
 ) -> Instruction {
-    spl_token::instruction::mint_to(&TOKEN_PROGRAM_ID, mint, destination, authority, &[], amount)
-        .unwrap()
+    match program {
+        TokenProgram::Legacy => spl_token::instruction::mint_to(
+            &TOKEN_PROGRAM_ID,
+            mint,
+            destination,
+            authority,
+            &[],
+            amount,
+        ),
+        TokenProgram::Token2022 => spl_token_2022_interface::instruction::mint_to(
+            &TOKEN_2022_PROGRAM_ID,
+            mint,
+            destination,
+            authority,
+            &[],
+            amount,
+        ),
+    }
+    .unwrap()
 }
 
+#[allow(deprecated)]
 pub fn transfer_instruction(
     source: &Pubkey,
     destination: &Pubkey,
     authority: &Pubkey,
     amount: u64,
+    program: TokenProgram,
 ) -> Instruction {
-    spl_token::instruction::transfer(
-        &TOKEN_PROGRAM_ID,
+    match program {
+        TokenProgram::Legacy => spl_token::instruction::transfer(
+            &TOKEN_PROGRAM_ID,
+            source,
+            destination,
+            authority,
+            &[],
+            amount,
+        ),
+        TokenProgram::Token2022 => spl_token_2022_interface::instruction::transfer(
+            &TOKEN_2022_PROGRAM_ID,
+            source,
+            destination,
+            authority,
+            &[],
+            amount,
+        ),
+    }
+    .unwrap()
+}
+
+pub fn transfer_checked_2022_instruction(
+    source: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+    decimals: u8,
+) -> Instruction {
+    spl_token_2022_interface::instruction::transfer_checked(
+        &TOKEN_2022_PROGRAM_ID,
         source,
+        mint,
         destination,
         authority,
         &[],
         amount,
+        decimals,
     )
     .unwrap()
 }
 
+pub fn approve_instruction(
+    source: &Pubkey,
+    delegate: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    spl_token::instruction::approve(&TOKEN_PROGRAM_ID, source, delegate, authority, &[], amount)
+        .unwrap()
+}
+
+pub fn revoke_instruction(source: &Pubkey, authority: &Pubkey) -> Instruction {
+    spl_token::instruction::revoke(&TOKEN_PROGRAM_ID, source, authority, &[]).unwrap()
+}
+
+pub fn burn_instruction(
+    mint: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    spl_token::instruction::burn(&TOKEN_PROGRAM_ID, account, mint, authority, &[], amount).unwrap()
+}
+
+pub fn burn_checked_instruction(
+    mint: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+    decimals: u8,
+) -> Instruction {
+    spl_token::instruction::burn_checked(
+        &TOKEN_PROGRAM_ID,
+        account,
+        mint,
+        authority,
+        &[],
+        amount,
+        decimals,
+    )
+    .unwrap()
+}
+
+pub fn freeze_account_instruction(
+    account: &Pubkey,
+    mint: &Pubkey,
+    freeze_authority: &Pubkey,
+) -> Instruction {
+    spl_token::instruction::freeze_account(&TOKEN_PROGRAM_ID, account, mint, freeze_authority, &[])
+        .unwrap()
+}
+
+pub fn thaw_account_instruction(
+    account: &Pubkey,
+    mint: &Pubkey,
+    freeze_authority: &Pubkey,
+) -> Instruction {
+    spl_token::instruction::thaw_account(&TOKEN_PROGRAM_ID, account, mint, freeze_authority, &[])
+        .unwrap()
+}
+
+pub fn close_account_instruction(
+    account: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    spl_token::instruction::close_account(&TOKEN_PROGRAM_ID, account, destination, authority, &[])
+        .unwrap()
+}
+
 pub fn sync_native_instruction(token_account: &Pubkey) -> Instruction {
     spl_token::instruction::sync_native(&TOKEN_PROGRAM_ID, token_account).unwrap()
 }
@@ -129,3 +419,58 @@ pub fn create_associated_token_account_instruction(
 pub fn get_associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
     spl_associated_token_account::get_associated_token_address(wallet, mint)
 }
+
+pub fn get_associated_token_address_for_program(
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    spl_associated_token_account::get_associated_token_address_with_program_id(
+        wallet,
+        mint,
+        token_program,
+    )
+}
+
+pub struct TokenSetup<'a> {
+    context: &'a MolluskContextHelper,
+    mint: Option<(Pubkey, u8)>,
+    holder: Option<(Pubkey, u64)>,
+}
+
+impl<'a> TokenSetup<'a> {
+    pub fn new(context: &'a MolluskContextHelper) -> Self {
+        Self {
+            context,
+            mint: None,
+            holder: None,
+        }
+    }
+
+    pub fn mint(mut self, authority: Pubkey, decimals: u8) -> Self {
+        self.mint = Some((authority, decimals));
+        self
+    }
+
+    pub fn holder(mut self, wallet: Pubkey, amount: u64) -> Self {
+        self.holder = Some((wallet, amount));
+        self
+    }
+
+    pub fn build(self) -> Result<(Pubkey, Pubkey)> {
+        let (authority, decimals) = self.mint.ok_or_else(|| {
+            MolluskHelperError::InvalidTokenSetup("mint(authority, decimals) not set".to_string())
+        })?;
+        let (wallet, amount) = self.holder.ok_or_else(|| {
+            MolluskHelperError::InvalidTokenSetup("holder(wallet, amount) not set".to_string())
+        })?;
+
+        let mint = Pubkey::new_unique();
+        self.context
+            .create_mint_with_options(&mint, &authority, decimals, None, amount);
+        let ata = self
+            .context
+            .create_associated_token_account_fixture(&wallet, &mint, &wallet, amount);
+        Ok((mint, ata))
+    }
+}
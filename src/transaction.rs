@@ -1,10 +1,21 @@
-use crate::context::MolluskContextHelper;
+use crate::context::{MolluskContextHelper, COMPUTE_BUDGET_PROGRAM_ID};
 use crate::error::{MolluskHelperError, Result};
 use mollusk_svm::result::InstructionResult;
+use solana_instruction::error::InstructionError;
 use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
 
 pub struct TransactionResult {
     pub instruction_results: Vec<InstructionResult>,
+    pub instruction_labels: Vec<String>,
+    pub instruction_logs: Vec<Vec<String>>,
     pub total_compute_units: u64,
     pub total_execution_time: u64,
 }
@@ -25,11 +36,132 @@ impl TransactionResult {
     pub fn last_result(&self) -> Option<&InstructionResult> {
         self.instruction_results.last()
     }
+
+    pub fn assert_log_count(&self, expected: usize) -> Result<()> {
+        let actual = self.logs().len();
+        if actual != expected {
+            return Err(MolluskHelperError::LogAssertionFailed(format!(
+                "expected {expected} log lines, got {actual}"
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn assert_instruction_count(&self, expected: usize) -> Result<()> {
+        let actual = self.instruction_results.len();
+        if actual != expected {
+            return Err(MolluskHelperError::AssertionFailed(format!(
+                "expected {expected} instructions, got {actual}"
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn logs(&self) -> Vec<String> {
+        self.instruction_logs.concat()
+    }
+
+    pub fn extract_anchor_events(&self) -> Vec<Vec<u8>> {
+        MolluskContextHelper::anchor_events_from_logs(&self.logs())
+    }
+
+    pub fn assert_log_contains(&self, substring: &str) -> Result<()> {
+        if !self.logs().iter().any(|line| line.contains(substring)) {
+            return Err(MolluskHelperError::LogAssertionFailed(format!(
+                "no log line contains {substring:?}"
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn assert_log_not_contains(&self, substring: &str) -> Result<()> {
+        if let Some(line) = self.logs().iter().find(|line| line.contains(substring)) {
+            return Err(MolluskHelperError::LogAssertionFailed(format!(
+                "unexpected log line containing {substring:?}: {line}"
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn compute_units_per_instruction(&self) -> Vec<u64> {
+        self.instruction_results
+            .iter()
+            .map(|r| r.compute_units_consumed)
+            .collect()
+    }
+
+    pub fn max_instruction_compute_units(&self) -> Option<u64> {
+        self.instruction_results
+            .iter()
+            .map(|r| r.compute_units_consumed)
+            .max()
+    }
+
+    pub fn summary(&self) -> String {
+        let status = if self.is_success() {
+            "succeeded"
+        } else {
+            "failed"
+        };
+        let mut summary = format!(
+            "Transaction {status}: {} instruction(s), {} compute units",
+            self.instruction_results.len(),
+            self.total_compute_units
+        );
+        for (index, (label, result)) in self
+            .instruction_labels
+            .iter()
+            .zip(&self.instruction_results)
+            .enumerate()
+        {
+            let outcome = match &result.raw_result {
+                Ok(()) => "success".to_string(),
+                Err(e) => format!("failed: {e}"),
+            };
+            let _ = write!(
+                summary,
+                "\n  #{index} {label}: {outcome} ({} CU)",
+                result.compute_units_consumed
+            );
+        }
+        summary
+    }
+
+    pub fn to_json(&self) -> String {
+        let instructions: Vec<serde_json::Value> = self
+            .instruction_results
+            .iter()
+            .enumerate()
+            .map(|(index, result)| {
+                serde_json::json!({
+                    "index": index,
+                    "label": self.instruction_labels.get(index),
+                    "success": result.program_result.is_ok(),
+                    "compute_units": result.compute_units_consumed,
+                    "execution_time": result.execution_time,
+                    "logs": self.instruction_logs.get(index).cloned().unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "success": self.is_success(),
+            "instruction_count": self.instruction_results.len(),
+            "total_compute_units": self.total_compute_units,
+            "total_execution_time": self.total_execution_time,
+            "instructions": instructions,
+        })
+        .to_string()
+    }
 }
 
 pub struct TransactionBuilder<'a> {
     context: &'a MolluskContextHelper,
     instructions: Vec<Instruction>,
+    instruction_names: HashMap<usize, String>,
+    fee: Option<(Pubkey, u64)>,
+    micro_lamports_per_cu: u64,
+    compute_unit_limit: Option<u32>,
 }
 
 impl<'a> TransactionBuilder<'a> {
@@ -37,6 +169,10 @@ impl<'a> TransactionBuilder<'a> {
         Self {
             context,
             instructions: Vec::new(),
+            instruction_names: HashMap::new(),
+            fee: None,
+            micro_lamports_per_cu: 0,
+            compute_unit_limit: None,
         }
     }
 
@@ -50,23 +186,140 @@ impl<'a> TransactionBuilder<'a> {
         self
     }
 
+    pub fn add_named_instruction(mut self, name: &str, instruction: Instruction) -> Self {
+        self.instruction_names
+            .insert(self.instructions.len(), name.to_string());
+        self.instructions.push(instruction);
+        self
+    }
+
+    pub fn with_payer_and_fee(mut self, payer: &Pubkey, lamports: u64) -> Self {
+        self.fee = Some((*payer, lamports));
+        self
+    }
+
+    pub fn with_priority_fee(mut self, micro_lamports_per_cu: u64) -> Self {
+        self.micro_lamports_per_cu = micro_lamports_per_cu;
+        self
+    }
+
+    pub fn with_compute_unit_limit(mut self, units: u32) -> Self {
+        self.compute_unit_limit = Some(units);
+        self
+    }
+
+    pub fn with_compute_unit_price(self, micro_lamports: u64) -> Self {
+        self.with_priority_fee(micro_lamports)
+    }
+
+    pub fn compute_budget_instructions(&self) -> Vec<Instruction> {
+        let mut instructions = Vec::new();
+        if let Some(units) = self.compute_unit_limit {
+            let mut data = vec![SET_COMPUTE_UNIT_LIMIT];
+            data.extend_from_slice(&units.to_le_bytes());
+            instructions.push(Instruction::new_with_bytes(
+                COMPUTE_BUDGET_PROGRAM_ID,
+                &data,
+                vec![],
+            ));
+        }
+        if self.micro_lamports_per_cu > 0 {
+            let mut data = vec![SET_COMPUTE_UNIT_PRICE];
+            data.extend_from_slice(&self.micro_lamports_per_cu.to_le_bytes());
+            instructions.push(Instruction::new_with_bytes(
+                COMPUTE_BUDGET_PROGRAM_ID,
+                &data,
+                vec![],
+            ));
+        }
+        instructions
+    }
+
+    fn compute_budget(&self) -> Option<u64> {
+        self.compute_unit_limit
+            .map(|units| u64::from(units.min(MAX_COMPUTE_UNIT_LIMIT)))
+    }
+
+    fn process(&self, instruction: &Instruction, budget: &mut Option<u64>) -> InstructionResult {
+        let Some(remaining) = budget else {
+            return self.context.process_instruction_internal(instruction);
+        };
+
+        let result = self
+            .context
+            .process_instruction_with_compute_unit_limit(instruction, *remaining);
+        *remaining = remaining.saturating_sub(result.compute_units_consumed);
+        result
+    }
+
+    fn priority_fee(&self, compute_units: u64) -> u64 {
+        let micro_lamports = u128::from(self.micro_lamports_per_cu) * u128::from(compute_units);
+        micro_lamports.div_ceil(1_000_000) as u64
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.instructions
+            .iter()
+            .enumerate()
+            .map(|(index, ix)| match self.instruction_names.get(&index) {
+                Some(name) => format!("'{name}'"),
+                None => self.context.program_label(&ix.program_id),
+            })
+            .collect()
+    }
+
     pub fn execute(self) -> Result<TransactionResult> {
+        self.execute_atomic().0
+    }
+
+    pub fn execute_with_logs(self) -> (Result<TransactionResult>, Vec<Vec<String>>) {
+        self.execute_atomic()
+    }
+
+    pub fn execute_expect_instruction_error(self, expected: InstructionError) -> Result<()> {
+        let (result, logs) = self.execute_atomic();
+        let outcome = match result {
+            Err(MolluskHelperError::TransactionFailed { error, .. }) if error == expected => {
+                return Ok(());
+            }
+            Err(MolluskHelperError::CpiDepthExceeded(_))
+                if expected == InstructionError::CallDepth =>
+            {
+                return Ok(());
+            }
+            Err(e) => format!("failed with {e}"),
+            Ok(_) => "succeeded".to_string(),
+        };
+        Err(MolluskHelperError::AssertionFailed(format!(
+            "expected {expected:?}, but the transaction {outcome}\nlogs:\n{}",
+            logs.concat().join("\n")
+        )))
+    }
+
+    fn execute_atomic(&self) -> (Result<TransactionResult>, Vec<Vec<String>>) {
         if self.instructions.is_empty() {
-            return Ok(TransactionResult {
+            let result = TransactionResult {
                 instruction_results: vec![],
+                instruction_labels: vec![],
+                instruction_logs: vec![],
                 total_compute_units: 0,
                 total_execution_time: 0,
-            });
+            };
+            return (Ok(result), vec![]);
         }
 
         let snapshot = self.context.snapshot_accounts();
+        let mut instruction_labels = self.labels();
+        let mut budget = self.compute_budget();
 
         let mut instruction_results = Vec::with_capacity(self.instructions.len());
+        let mut instruction_logs = Vec::with_capacity(self.instructions.len());
         let mut total_compute_units = 0u64;
         let mut total_execution_time = 0u64;
 
         for (index, instruction) in self.instructions.iter().enumerate() {
-            let result = self.context.process_instruction_internal(instruction);
+            let result = self.process(instruction, &mut budget);
+            instruction_logs.push(self.context.logs());
 
             total_compute_units += result.compute_units_consumed;
             total_execution_time += result.execution_time;
@@ -79,37 +332,84 @@ impl<'a> TransactionBuilder<'a> {
                     Ok(_) => unreachable!(),
                 };
 
-                return Err(MolluskHelperError::TransactionFailed { index, error });
+                let err = if error == InstructionError::CallDepth {
+                    MolluskHelperError::CpiDepthExceeded(self.context.max_cpi_depth())
+                } else {
+                    MolluskHelperError::TransactionFailed {
+                        index,
+                        label: instruction_labels.swap_remove(index),
+                        error,
+                    }
+                };
+                return (Err(err), instruction_logs);
             }
 
             instruction_results.push(result);
         }
 
-        Ok(TransactionResult {
+        if let Some((payer, base_fee)) = &self.fee {
+            let fee = base_fee + self.priority_fee(total_compute_units);
+            if let Err(e) = self.context.deduct_fee(payer, fee) {
+                self.context.restore_accounts(snapshot);
+                return (Err(e), instruction_logs);
+            }
+        }
+
+        let result = TransactionResult {
             instruction_results,
+            instruction_labels,
+            instruction_logs: instruction_logs.clone(),
             total_compute_units,
             total_execution_time,
-        })
+        };
+        (Ok(result), instruction_logs)
+    }
+
+    pub fn execute_with_csv(self, path: impl AsRef<Path>) -> Result<TransactionResult> {
+        let program_ids: Vec<Pubkey> = self.instructions.iter().map(|ix| ix.program_id).collect();
+        let result = self.execute()?;
+
+        let mut csv = String::from("index,program_id,compute_units,execution_time\n");
+        for (index, (program_id, instruction_result)) in program_ids
+            .iter()
+            .zip(&result.instruction_results)
+            .enumerate()
+        {
+            let _ = writeln!(
+                csv,
+                "{index},{program_id},{},{}",
+                instruction_result.compute_units_consumed, instruction_result.execution_time
+            );
+        }
+        std::fs::write(path, csv)?;
+
+        Ok(result)
     }
 
     pub fn execute_allow_failures(self) -> TransactionResult {
         if self.instructions.is_empty() {
             return TransactionResult {
                 instruction_results: vec![],
+                instruction_labels: vec![],
+                instruction_logs: vec![],
                 total_compute_units: 0,
                 total_execution_time: 0,
             };
         }
 
         let snapshot = self.context.snapshot_accounts();
+        let instruction_labels = self.labels();
+        let mut budget = self.compute_budget();
 
         let mut instruction_results = Vec::with_capacity(self.instructions.len());
+        let mut instruction_logs = Vec::with_capacity(self.instructions.len());
         let mut total_compute_units = 0u64;
         let mut total_execution_time = 0u64;
         let mut any_failed = false;
 
         for instruction in &self.instructions {
-            let result = self.context.process_instruction_internal(instruction);
+            let result = self.process(instruction, &mut budget);
+            instruction_logs.push(self.context.logs());
 
             total_compute_units += result.compute_units_consumed;
             total_execution_time += result.execution_time;
@@ -127,28 +427,50 @@ impl<'a> TransactionBuilder<'a> {
 
         TransactionResult {
             instruction_results,
+            instruction_labels,
+            instruction_logs,
             total_compute_units,
             total_execution_time,
         }
     }
 
+    pub fn run_on_fork(self) -> (MolluskContextHelper, TransactionResult) {
+        let fork = self.context.fork();
+        let result = TransactionBuilder {
+            context: &fork,
+            instructions: self.instructions,
+            instruction_names: self.instruction_names,
+            fee: self.fee,
+            micro_lamports_per_cu: self.micro_lamports_per_cu,
+            compute_unit_limit: self.compute_unit_limit,
+        }
+        .execute_allow_failures();
+        (fork, result)
+    }
+
     pub fn dry_run(self) -> TransactionResult {
         if self.instructions.is_empty() {
             return TransactionResult {
                 instruction_results: vec![],
+                instruction_labels: vec![],
+                instruction_logs: vec![],
                 total_compute_units: 0,
                 total_execution_time: 0,
             };
         }
 
         let snapshot = self.context.snapshot_accounts();
+        let mut instruction_labels = self.labels();
+        let mut budget = self.compute_budget();
 
         let mut instruction_results = Vec::with_capacity(self.instructions.len());
+        let mut instruction_logs = Vec::with_capacity(self.instructions.len());
         let mut total_compute_units = 0u64;
         let mut total_execution_time = 0u64;
 
         for instruction in &self.instructions {
-            let result = self.context.process_instruction_internal(instruction);
+            let result = self.process(instruction, &mut budget);
+            instruction_logs.push(self.context.logs());
 
             total_compute_units += result.compute_units_consumed;
             total_execution_time += result.execution_time;
@@ -162,9 +484,12 @@ impl<'a> TransactionBuilder<'a> {
         }
 
         self.context.restore_accounts(snapshot);
+        instruction_labels.truncate(instruction_results.len());
 
         TransactionResult {
             instruction_results,
+            instruction_labels,
+            instruction_logs,
             total_compute_units,
             total_execution_time,
         }
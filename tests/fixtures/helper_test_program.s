@@ -0,0 +1,96 @@
+# SBPFv3 test program used by tests/integration_test.rs.
+# Instruction data: [op, args...].
+#   op 0: log data[1..] three times with sol_log_
+#   op 1: emit data[1..] as a single sol_log_data event
+#   op 2: abort
+#   op 3: invoke itself with the same data, recursing until the CPI depth
+#         limit; the first account must be this program
+#   op 4: spin for u32 data[1..5] loop iterations (two instructions each)
+entrypoint:
+    add64 r10, -64
+    ldxdw r3, [r1+0]
+    add64 r1, 8
+# skip the serialized accounts so r1 points at the instruction data length
+skip_accounts:
+    jeq r3, 0, parsed
+    ldxb r4, [r1+0]
+    jne r4, 255, duplicate_account
+    ldxdw r4, [r1+80]
+    add64 r4, 10335
+    and64 r4, -8
+    add64 r4, 8
+    add64 r1, r4
+    ja next_account
+duplicate_account:
+    add64 r1, 8
+next_account:
+    add64 r3, -1
+    ja skip_accounts
+parsed:
+    ldxb r2, [r1+8]
+    jeq r2, 0, log_lines
+    jeq r2, 1, emit_event
+    jeq r2, 2, do_abort
+    jeq r2, 3, self_invoke
+    jeq r2, 4, burn
+    mov64 r0, 1
+    return
+log_lines:
+    ldxdw r7, [r1+0]
+    add64 r7, -1
+    mov64 r6, r1
+    add64 r6, 9
+    mov64 r1, r6
+    mov64 r2, r7
+    syscall sol_log_
+    mov64 r1, r6
+    mov64 r2, r7
+    syscall sol_log_
+    mov64 r1, r6
+    mov64 r2, r7
+    syscall sol_log_
+    mov64 r0, 0
+    return
+emit_event:
+    ldxdw r2, [r1+0]
+    add64 r2, -1
+    mov64 r3, r1
+    add64 r3, 9
+    stxdw [r10+0], r3
+    stxdw [r10+8], r2
+    mov64 r1, r10
+    mov64 r2, 1
+    syscall sol_log_data
+    mov64 r0, 0
+    return
+do_abort:
+    syscall abort
+    return
+self_invoke:
+    ldxdw r2, [r1+0]
+    mov64 r3, r1
+    add64 r3, 8
+    stxdw [r10+24], r3
+    stxdw [r10+32], r2
+    add64 r3, r2
+    stxdw [r10+0], r3
+    stxdw [r10+40], r3
+    stdw [r10+48], 0
+    mov64 r4, r10
+    add64 r4, 40
+    stxdw [r10+8], r4
+    stdw [r10+16], 1
+    mov64 r1, r10
+    mov64 r2, 0
+    mov64 r3, 0
+    mov64 r4, 0
+    mov64 r5, 0
+    syscall sol_invoke_signed_c
+    return
+burn:
+    ldxw r2, [r1+9]
+burn_loop:
+    add64 r2, -1
+    jne r2, 0, burn_loop
+    mov64 r0, 0
+    return
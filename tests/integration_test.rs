@@ -1,4 +1,7 @@
 use mollusk_helper::prelude::*;
+use solana_instruction::error::InstructionError;
+
+const TEST_PROGRAM_ELF: &[u8] = include_bytes!("fixtures/helper_test_program.so");
 
 #[test]
 fn test_constructor_variants() {
@@ -213,3 +216,2877 @@ fn test_execute_allow_failures() {
     assert_eq!(ctx.get_balance(&alice), Some(100_000));
     assert_eq!(ctx.get_balance(&bob), Some(100_000));
 }
+
+#[test]
+fn test_close_account_manually() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let program = Pubkey::new_unique();
+    let data_account = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+
+    ctx.add_program_account(&data_account, &program, vec![7u8; 64]);
+    ctx.fund_account(&destination, 5_000);
+
+    assert!(ctx.assert_account_closed(&data_account).is_err());
+
+    ctx.close_account_manually(&data_account, &destination)
+        .unwrap();
+
+    ctx.assert_account_closed(&data_account).unwrap();
+    assert_eq!(ctx.get_balance(&destination), Some(1_000_005_000));
+
+    let closed = ctx.get_account(&data_account).unwrap();
+    assert_eq!(closed.owner, MolluskContextHelper::system_program());
+    assert!(closed.data.is_empty());
+}
+
+#[test]
+fn test_close_account_manually_into_itself_fails() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let program = Pubkey::new_unique();
+    let data_account = Pubkey::new_unique();
+
+    ctx.add_program_account(&data_account, &program, vec![7u8; 64]);
+    let before = ctx.get_account(&data_account).unwrap();
+
+    assert!(matches!(
+        ctx.close_account_manually(&data_account, &data_account),
+        Err(MolluskHelperError::InvalidCloseDestination(_))
+    ));
+    assert_eq!(ctx.get_account(&data_account).unwrap(), before);
+}
+
+#[test]
+fn test_assert_account_size() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let program = Pubkey::new_unique();
+    let account = Pubkey::new_unique();
+
+    ctx.add_program_account(&account, &program, vec![0u8; 128]);
+
+    ctx.assert_account_size(&account, 128).unwrap();
+
+    match ctx.assert_account_size(&account, 96) {
+        Err(MolluskHelperError::UnexpectedAccountSize { expected, actual }) => {
+            assert_eq!(expected, 96);
+            assert_eq!(actual, 128);
+        }
+        other => panic!("expected UnexpectedAccountSize, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_log_byte_limit_truncates_logs() {
+    let mut ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    ctx.transfer_sol(&alice, &bob, 1_000).unwrap();
+    let logs = ctx.logs();
+    assert!(logs
+        .iter()
+        .any(|line| line.starts_with("Program 11111111111111111111111111111111 invoke")));
+    assert!(!logs.iter().any(|line| line == "Log truncated"));
+
+    ctx.set_log_byte_limit(16);
+
+    ctx.transfer_sol(&alice, &bob, 1_000).unwrap();
+    assert_eq!(ctx.logs(), vec!["Log truncated".to_string()]);
+}
+
+#[test]
+fn test_run_repeated_idempotent_create_ata() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let payer = Pubkey::new_unique();
+    let wallet = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let mint_authority = Pubkey::new_unique();
+
+    ctx.fund_account(&payer, 1_000_000_000);
+    ctx.create_mint(&mint, &mint_authority, 6);
+
+    let ix =
+        MolluskContextHelper::create_associated_token_account_instruction(&payer, &wallet, &mint);
+    let ata = ctx.get_associated_token_address(&wallet, &mint);
+
+    let first = ctx.run_repeated(&ix, 1);
+    assert!(first[0].program_result.is_ok());
+
+    let ata_after_first = ctx.get_account(&ata).unwrap();
+    let payer_after_first = ctx.get_balance(&payer).unwrap();
+
+    let results = ctx.run_repeated(&ix, 2);
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.program_result.is_ok()));
+
+    assert_eq!(ctx.get_account(&ata).unwrap(), ata_after_first);
+    assert_eq!(ctx.get_balance(&payer).unwrap(), payer_after_first);
+    assert_eq!(ctx.get_token_balance(&ata).unwrap(), 0);
+}
+
+#[test]
+fn test_bulk_pubkey_address_conversion() {
+    let pubkeys: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+
+    let addresses = MolluskContextHelper::pubkeys_to_addresses(&pubkeys);
+    assert_eq!(addresses.len(), pubkeys.len());
+    for (pubkey, address) in pubkeys.iter().zip(&addresses) {
+        assert_eq!(*address, MolluskContextHelper::pubkey_to_address(pubkey));
+    }
+
+    let round_trip = MolluskContextHelper::addresses_to_pubkeys(&addresses);
+    assert_eq!(round_trip, pubkeys);
+}
+
+#[test]
+fn test_assert_tx_fits() {
+    let payer = Pubkey::new_unique();
+
+    let small = vec![solana_system_interface::instruction::transfer(
+        &payer,
+        &Pubkey::new_unique(),
+        1,
+    )];
+    MolluskContextHelper::assert_tx_fits(&payer, &small, &[]).unwrap();
+
+    let oversized: Vec<Instruction> = (0..40)
+        .map(|_| solana_system_interface::instruction::transfer(&payer, &Pubkey::new_unique(), 1))
+        .collect();
+    match MolluskContextHelper::assert_tx_fits(&payer, &oversized, &[]) {
+        Err(MolluskHelperError::TransactionTooLarge { size, limit }) => {
+            assert_eq!(limit, 1232);
+            assert!(size > limit);
+        }
+        other => panic!("expected TransactionTooLarge, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_account_template() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let program = Pubkey::new_unique();
+    let template = AccountTemplate::new()
+        .lamports(2_000_000)
+        .owner(program)
+        .data(vec![1, 2, 3, 4]);
+
+    let first = Pubkey::new_unique();
+    let second = Pubkey::new_unique();
+
+    ctx.add_account_from_template(&first, &template);
+    ctx.add_account_from_template(&second, &template);
+
+    let first_account = ctx.get_account(&first).unwrap();
+    let second_account = ctx.get_account(&second).unwrap();
+
+    assert_eq!(first_account, second_account);
+    assert_eq!(first_account, template.build());
+    assert_eq!(first_account.lamports, 2_000_000);
+    assert_eq!(first_account.owner, program);
+    assert_eq!(first_account.data, vec![1, 2, 3, 4]);
+    assert!(!first_account.executable);
+}
+
+#[test]
+fn test_get_freeze_authority() {
+    use solana_program_option::COption;
+    use solana_program_pack::Pack;
+
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let mint_authority = Pubkey::new_unique();
+    let freeze_authority = Pubkey::new_unique();
+
+    let state = spl_token::state::Mint {
+        mint_authority: COption::Some(mint_authority),
+        supply: 0,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::Some(freeze_authority),
+    };
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint::pack(state, &mut data).unwrap();
+    ctx.add_program_account(&mint, &MolluskContextHelper::token_program(), data);
+
+    assert_eq!(
+        ctx.get_freeze_authority(&mint).unwrap(),
+        Some(freeze_authority)
+    );
+
+    let plain_mint = Pubkey::new_unique();
+    ctx.create_mint(&plain_mint, &mint_authority, 6);
+    assert_eq!(ctx.get_freeze_authority(&plain_mint).unwrap(), None);
+}
+
+#[test]
+fn test_assert_account_unchanged() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    let carol = Pubkey::new_unique();
+
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+    ctx.fund_account(&carol, 42_000);
+
+    let alice_before = ctx.get_account(&alice).unwrap();
+    let carol_before = ctx.get_account(&carol).unwrap();
+
+    let result = ctx.transfer_sol(&alice, &bob, 250_000).unwrap();
+
+    ctx.assert_account_unchanged(&carol, &carol_before, &result)
+        .unwrap();
+    assert!(ctx
+        .assert_account_unchanged(&alice, &alice_before, &result)
+        .is_err());
+}
+
+#[test]
+fn test_create_data_accounts_rent_exempt() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let owner = Pubkey::new_unique();
+    let specs: Vec<(Pubkey, Pubkey, Vec<u8>)> = [0usize, 100, 1_000]
+        .iter()
+        .map(|len| (Pubkey::new_unique(), owner, vec![0u8; *len]))
+        .collect();
+
+    ctx.create_data_accounts(&specs).unwrap();
+
+    for (pubkey, owner, data) in &specs {
+        let account = ctx.get_account(pubkey).unwrap();
+        assert_eq!(account.owner, *owner);
+        assert_eq!(account.data.len(), data.len());
+        assert_eq!(
+            account.lamports,
+            ctx.minimum_balance_for_rent_exemption(data.len())
+        );
+    }
+    assert!(
+        ctx.minimum_balance_for_rent_exemption(1_000) > ctx.minimum_balance_for_rent_exemption(0)
+    );
+}
+
+#[test]
+fn test_run_on_fork_leaves_original_untouched() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let ix = solana_system_interface::instruction::transfer(&alice, &bob, 400_000);
+
+    let (fork, result) = ctx.transaction().add_instruction(ix).run_on_fork();
+
+    assert!(result.is_success());
+    assert_eq!(fork.get_balance(&alice), Some(600_000));
+    assert_eq!(fork.get_balance(&bob), Some(400_000));
+
+    assert_eq!(ctx.get_balance(&alice), Some(1_000_000));
+    assert_eq!(ctx.get_balance(&bob), Some(0));
+
+    fork.transfer_sol(&bob, &alice, 100_000).unwrap();
+    assert_eq!(fork.get_balance(&bob), Some(300_000));
+    assert_eq!(ctx.get_balance(&bob), Some(0));
+}
+
+#[test]
+fn test_fork_shares_program_elf() {
+    let program_id = Pubkey::new_unique();
+    let ctx = MolluskContextHelper::new(&program_id, mollusk_svm_programs_memo::memo::ELF);
+    let fork = ctx.fork();
+
+    assert!(std::sync::Arc::ptr_eq(
+        &ctx.program_elf(&program_id).unwrap(),
+        &fork.program_elf(&program_id).unwrap()
+    ));
+    assert!(fork.program_elf(&Pubkey::new_unique()).is_none());
+
+    let ix = Instruction::new_with_bytes(program_id, b"forked", vec![]);
+    assert!(fork.process_instruction(&ix).is_ok());
+}
+
+#[test]
+fn test_native_token_account_rent_reserve() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let owner = Pubkey::new_unique();
+    let wsol = Pubkey::new_unique();
+    let amount = 5_000_000_000;
+
+    ctx.create_native_token_account(&wsol, &owner, amount);
+
+    let reserve = ctx.minimum_balance_for_rent_exemption(165);
+    assert_eq!(ctx.get_balance(&wsol), Some(amount + reserve));
+    assert_eq!(ctx.get_token_balance(&wsol).unwrap(), amount);
+    assert_eq!(ctx.get_wrapped_sol_spendable(&wsol).unwrap(), amount);
+
+    let mint = Pubkey::new_unique();
+    let plain = Pubkey::new_unique();
+    ctx.create_mint(&mint, &owner, 9);
+    ctx.create_token_account(&plain, &mint, &owner, 10);
+    assert!(matches!(
+        ctx.get_wrapped_sol_spendable(&plain),
+        Err(MolluskHelperError::NotNativeTokenAccount(_))
+    ));
+}
+
+#[test]
+fn test_modified_since_checkpoint() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    let carol = Pubkey::new_unique();
+
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+    ctx.fund_account(&carol, 10);
+
+    ctx.checkpoint("funded").unwrap();
+    assert!(ctx.modified_since_checkpoint("funded").unwrap().is_empty());
+
+    ctx.transfer_sol(&alice, &bob, 300_000).unwrap();
+
+    let diffs = ctx.modified_since_checkpoint("funded").unwrap();
+    assert_eq!(diffs.len(), 2);
+
+    let alice_diff = diffs.iter().find(|d| d.pubkey == alice).unwrap();
+    assert_eq!(alice_diff.old_lamports, 1_000_000);
+    assert_eq!(alice_diff.new_lamports, 700_000);
+    assert!(!alice_diff.data_changed);
+
+    let bob_diff = diffs.iter().find(|d| d.pubkey == bob).unwrap();
+    assert_eq!(bob_diff.lamport_delta(), 300_000);
+
+    assert!(matches!(
+        ctx.modified_since_checkpoint("missing"),
+        Err(MolluskHelperError::CheckpointNotFound(_))
+    ));
+}
+
+#[test]
+fn test_process_instruction_as_charges_payer() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let payer = Pubkey::new_unique();
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+
+    ctx.fund_account(&payer, 1_000_000);
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let ix = solana_system_interface::instruction::transfer(&alice, &bob, 100_000);
+    ctx.process_instruction_as(&ix, &payer).unwrap();
+
+    assert_eq!(
+        ctx.get_balance(&payer),
+        Some(1_000_000 - DEFAULT_LAMPORTS_PER_SIGNATURE)
+    );
+    assert_eq!(ctx.get_balance(&alice), Some(900_000));
+    assert_eq!(ctx.get_balance(&bob), Some(100_000));
+
+    let broke = Pubkey::new_unique();
+    ctx.fund_account(&broke, 1);
+    let ix = solana_system_interface::instruction::transfer(&alice, &bob, 100_000);
+    assert!(matches!(
+        ctx.process_instruction_as(&ix, &broke),
+        Err(MolluskHelperError::InsufficientFunds { have: 1, .. })
+    ));
+    assert_eq!(ctx.get_balance(&alice), Some(900_000));
+}
+
+#[test]
+fn test_assert_supply_conserved() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let first = Pubkey::new_unique();
+    let second = Pubkey::new_unique();
+
+    ctx.create_mint(&mint, &authority, 6);
+    ctx.create_token_account(&first, &mint, &Pubkey::new_unique(), 0);
+    ctx.create_token_account(&second, &mint, &Pubkey::new_unique(), 0);
+
+    ctx.mint_to(&mint, &first, &authority, 700).unwrap();
+    ctx.mint_to(&mint, &second, &authority, 300).unwrap();
+
+    ctx.assert_supply_conserved(&mint).unwrap();
+
+    let other_mint = Pubkey::new_unique();
+    ctx.create_mint(&other_mint, &authority, 6);
+    ctx.create_token_account(&Pubkey::new_unique(), &other_mint, &authority, 50);
+    ctx.assert_supply_conserved(&mint).unwrap();
+
+    ctx.create_token_account(&Pubkey::new_unique(), &mint, &authority, 50);
+    assert!(ctx.assert_supply_conserved(&mint).is_err());
+}
+
+#[test]
+fn test_execute_with_csv() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let path = std::env::temp_dir().join(format!("mollusk_helper_{}.csv", Pubkey::new_unique()));
+
+    let result = ctx
+        .transaction()
+        .add_instruction(solana_system_interface::instruction::transfer(
+            &alice, &bob, 1_000,
+        ))
+        .add_instruction(solana_system_interface::instruction::transfer(
+            &alice, &bob, 2_000,
+        ))
+        .execute_with_csv(&path)
+        .unwrap();
+    assert!(result.is_success());
+
+    let csv = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("index,program_id,compute_units,execution_time")
+    );
+    let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+    assert_eq!(rows.len(), 2);
+    for (index, row) in rows.iter().enumerate() {
+        assert_eq!(row[0], index.to_string());
+        assert_eq!(row[1], MolluskContextHelper::system_program().to_string());
+        assert!(row[2].parse::<u64>().unwrap() > 0);
+    }
+}
+
+#[test]
+fn test_close_token_account_reclaims_lamports() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+
+    ctx.create_mint(&mint, &owner, 6);
+    ctx.create_token_account(&token_account, &mint, &owner, 0);
+    ctx.fund_account(&destination, 1_000);
+
+    let prior_lamports = ctx.get_balance(&token_account).unwrap();
+
+    let (result, reclaimed) = ctx
+        .close_token_account(&token_account, &destination, &owner)
+        .unwrap();
+
+    assert!(result.program_result.is_ok());
+    assert_eq!(reclaimed, prior_lamports);
+    assert_eq!(ctx.get_balance(&destination), Some(1_000 + prior_lamports));
+}
+
+#[test]
+fn test_new_named_labels_summary_and_errors() {
+    let program_id = Pubkey::new_unique();
+    let ctx = MolluskContextHelper::new_named(
+        "MyMemo",
+        &program_id,
+        mollusk_svm_programs_memo::memo::ELF,
+    );
+
+    let memo_ix = Instruction::new_with_bytes(program_id, b"hello", vec![]);
+    let result = ctx
+        .transaction()
+        .add_instruction(memo_ix)
+        .execute()
+        .unwrap();
+
+    let summary = result.summary();
+    assert!(summary.contains("program MyMemo"), "{summary}");
+
+    let invalid_memo_ix = Instruction::new_with_bytes(program_id, &[0xff, 0xfe], vec![]);
+    let err = ctx
+        .transaction()
+        .add_instruction(invalid_memo_ix)
+        .execute()
+        .unwrap_err();
+    assert!(err.to_string().contains("program MyMemo"), "{err}");
+}
+
+#[test]
+fn test_assert_became_rent_exempt() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let funder = Pubkey::new_unique();
+    let account = Pubkey::new_unique();
+    ctx.fund_account(&funder, 10_000_000);
+    ctx.fund_account(&account, 1_000);
+
+    let before = ctx.get_account(&account).unwrap();
+    let topup = ctx.minimum_balance_for_rent_exemption(0);
+    ctx.transfer_sol(&funder, &account, topup).unwrap();
+
+    ctx.assert_became_rent_exempt(&account, &before).unwrap();
+
+    let already_exempt = ctx.get_account(&account).unwrap();
+    assert!(ctx
+        .assert_became_rent_exempt(&account, &already_exempt)
+        .is_err());
+}
+
+#[test]
+fn test_accounts_sorted_is_deterministic() {
+    let first = MolluskContextHelper::new_without_program_with_timestamp(1_000);
+    let second = MolluskContextHelper::new_without_program_with_timestamp(1_000);
+
+    let pubkeys: Vec<Pubkey> = (0..8).map(|_| Pubkey::new_unique()).collect();
+    for (index, pubkey) in pubkeys.iter().enumerate() {
+        first.fund_account(pubkey, 1_000 + index as u64);
+    }
+    for (index, pubkey) in pubkeys.iter().enumerate().rev() {
+        second.fund_account(pubkey, 1_000 + index as u64);
+    }
+
+    let first_sorted = first.accounts_sorted();
+    let second_sorted = second.accounts_sorted();
+
+    assert_eq!(first_sorted, second_sorted);
+    assert_eq!(first_sorted.len(), first.accounts_snapshot().len());
+    assert!(first_sorted
+        .windows(2)
+        .all(|pair| pair[0].0.to_bytes() < pair[1].0.to_bytes()));
+}
+
+#[test]
+fn test_fund_account_boundaries() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let sender = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let amount = 1_000_000;
+    ctx.fund_account(&recipient, 0);
+
+    ctx.fund_account_just_below(&sender, amount);
+    assert_eq!(ctx.get_balance(&sender), Some(amount - 1));
+    assert!(ctx.transfer_sol(&sender, &recipient, amount).is_err());
+
+    ctx.fund_account_exactly(&sender, amount);
+    assert_eq!(ctx.get_balance(&sender), Some(amount));
+    ctx.transfer_sol(&sender, &recipient, amount).unwrap();
+    assert_eq!(ctx.get_balance(&recipient), Some(amount));
+}
+
+#[test]
+fn test_snapshot_and_restore_token_accounts() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+
+    ctx.create_mint(&mint, &authority, 6);
+    ctx.create_token_account(&token_account, &mint, &authority, 0);
+    ctx.mint_to(&mint, &token_account, &authority, 1_000)
+        .unwrap();
+
+    let snapshot = ctx.snapshot_token_accounts();
+    assert_eq!(snapshot.len(), 1);
+    let info = &snapshot[&token_account];
+    assert_eq!(info.amount, 1_000);
+    assert_eq!(info.state, TokenAccountState::Initialized);
+
+    ctx.mint_to(&mint, &token_account, &authority, 500).unwrap();
+    assert_eq!(ctx.get_token_balance(&token_account).unwrap(), 1_500);
+
+    ctx.restore_token_accounts(&snapshot).unwrap();
+    assert_eq!(ctx.get_token_balance(&token_account).unwrap(), 1_000);
+}
+
+#[test]
+fn test_get_upgrade_authority() {
+    let mut ctx = MolluskContextHelper::new_without_program();
+
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    ctx.add_program_with_upgrade_authority(
+        &program_id,
+        mollusk_svm_programs_memo::memo::ELF,
+        &authority,
+    );
+
+    assert_eq!(
+        ctx.get_upgrade_authority(&program_id).unwrap(),
+        Some(authority)
+    );
+    assert!(ctx.get_upgrade_authority(&Pubkey::new_unique()).is_err());
+}
+
+#[test]
+fn test_remove_program() {
+    let mut ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    ctx.create_mint(&mint, &authority, 6);
+    ctx.create_token_account(&token_account, &mint, &authority, 0);
+
+    ctx.remove_program(&MolluskContextHelper::token_program())
+        .unwrap();
+    assert!(ctx
+        .get_account(&MolluskContextHelper::token_program())
+        .is_none());
+
+    let err = ctx
+        .mint_to(&mint, &token_account, &authority, 1_000)
+        .unwrap_err();
+    assert!(matches!(err, MolluskHelperError::ProgramNotLoaded(_)));
+    assert!(matches!(
+        ctx.remove_program(&MolluskContextHelper::token_program()),
+        Err(MolluskHelperError::ProgramNotLoaded(_))
+    ));
+
+    let sender = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    ctx.fund_account(&sender, 1_000_000);
+    ctx.fund_account(&recipient, 0);
+    ctx.transfer_sol(&sender, &recipient, 1_000).unwrap();
+}
+
+#[test]
+fn test_associated_token_address_for_program() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let wallet = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+
+    let classic = ctx.get_associated_token_address_for_program(
+        &wallet,
+        &mint,
+        &MolluskContextHelper::token_program(),
+    );
+    let token_2022 = ctx.get_associated_token_address_for_program(
+        &wallet,
+        &mint,
+        &MolluskContextHelper::token_2022_program(),
+    );
+
+    assert_ne!(classic, token_2022);
+    assert_eq!(classic, ctx.get_associated_token_address(&wallet, &mint));
+}
+
+#[test]
+fn test_execute_with_logs_keeps_logs_on_failure() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let (result, logs) = ctx
+        .transaction()
+        .add_instruction(solana_system_interface::instruction::transfer(
+            &alice, &bob, 1_000,
+        ))
+        .add_instruction(solana_system_interface::instruction::transfer(
+            &alice, &bob, 10_000_000,
+        ))
+        .execute_with_logs();
+
+    assert!(matches!(
+        result,
+        Err(MolluskHelperError::TransactionFailed { index: 1, .. })
+    ));
+    assert_eq!(logs.len(), 2);
+    assert!(logs[0]
+        .iter()
+        .any(|line| line == "Program 11111111111111111111111111111111 success"));
+    assert_eq!(ctx.get_balance(&bob), Some(0));
+}
+
+#[test]
+fn test_assert_pubkey_address_consistent() {
+    let pubkey = Pubkey::new_unique();
+    let address = MolluskContextHelper::pubkey_to_address(&pubkey);
+    MolluskContextHelper::assert_pubkey_address_consistent(&pubkey, &address).unwrap();
+
+    let other = MolluskContextHelper::pubkey_to_address(&Pubkey::new_unique());
+    assert!(MolluskContextHelper::assert_pubkey_address_consistent(&pubkey, &other).is_err());
+}
+
+#[test]
+fn test_atomic_swap() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint_x = Pubkey::new_unique();
+    let mint_y = Pubkey::new_unique();
+    let alice = Pubkey::new_unique();
+    let pool = Pubkey::new_unique();
+
+    let alice_x = Pubkey::new_unique();
+    let alice_y = Pubkey::new_unique();
+    let pool_x = Pubkey::new_unique();
+    let pool_y = Pubkey::new_unique();
+
+    ctx.create_mint(&mint_x, &alice, 6);
+    ctx.create_mint(&mint_y, &pool, 6);
+    ctx.create_token_account(&alice_x, &mint_x, &alice, 1_000);
+    ctx.create_token_account(&alice_y, &mint_y, &alice, 0);
+    ctx.create_token_account(&pool_x, &mint_x, &pool, 0);
+    ctx.create_token_account(&pool_y, &mint_y, &pool, 2_000);
+
+    let result = ctx
+        .atomic_swap(
+            (&alice_x, &pool_x, &alice, 400),
+            (&pool_y, &alice_y, &pool, 800),
+        )
+        .unwrap();
+    assert_eq!(result.instruction_results.len(), 2);
+
+    assert_eq!(ctx.get_token_balance(&alice_x).unwrap(), 600);
+    assert_eq!(ctx.get_token_balance(&pool_x).unwrap(), 400);
+    assert_eq!(ctx.get_token_balance(&pool_y).unwrap(), 1_200);
+    assert_eq!(ctx.get_token_balance(&alice_y).unwrap(), 800);
+
+    assert!(ctx
+        .atomic_swap(
+            (&alice_x, &pool_x, &alice, 100),
+            (&pool_y, &alice_y, &pool, 5_000)
+        )
+        .is_err());
+    assert_eq!(ctx.get_token_balance(&alice_x).unwrap(), 600);
+}
+
+#[test]
+fn test_max_cpi_depth() {
+    let program_id = Pubkey::new_unique();
+    let mut ctx = MolluskContextHelper::new(&program_id, TEST_PROGRAM_ELF);
+
+    let ix = Instruction::new_with_bytes(
+        program_id,
+        &[3],
+        vec![AccountMeta::new_readonly(program_id, false)],
+    );
+
+    let limit = ctx.max_cpi_depth();
+    assert!(matches!(
+        ctx.process_instruction(&ix),
+        Err(MolluskHelperError::CpiDepthExceeded(depth)) if depth == limit
+    ));
+
+    ctx.set_max_cpi_depth(2);
+    assert_eq!(ctx.max_cpi_depth(), 2);
+    assert!(matches!(
+        ctx.process_instruction(&ix),
+        Err(MolluskHelperError::CpiDepthExceeded(2))
+    ));
+    assert!(matches!(
+        ctx.transaction().add_instruction(ix.clone()).execute(),
+        Err(MolluskHelperError::CpiDepthExceeded(2))
+    ));
+    ctx.transaction()
+        .add_instruction(ix)
+        .execute_expect_instruction_error(InstructionError::CallDepth)
+        .unwrap();
+}
+
+#[cfg(feature = "rpc")]
+#[test]
+fn test_clone_account_from_rpc() {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let pubkey = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let rpc_url = format!("http://{}", listener.local_addr().unwrap());
+
+    let body = format!(
+        r#"{{"jsonrpc":"2.0","id":1,"result":{{"context":{{"slot":1}},"value":{{"lamports":42000,"owner":"{owner}","data":["AQID","base64"],"executable":false,"rentEpoch":18446744073709551615,"space":3}}}}}}"#
+    );
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        loop {
+            let read = stream.read(&mut buf).unwrap();
+            request.extend_from_slice(&buf[..read]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(header_end) = text.find("\r\n\r\n") {
+                let content_length = text[..header_end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if request.len() >= header_end + 4 + content_length {
+                    break;
+                }
+            }
+        }
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+        String::from_utf8(request).unwrap()
+    });
+
+    let ctx = MolluskContextHelper::new_without_program();
+    ctx.clone_account_from_rpc(&rpc_url, &pubkey).unwrap();
+
+    let request = server.join().unwrap();
+    assert!(request.contains("getAccountInfo"));
+    assert!(request.contains(&pubkey.to_string()));
+
+    let account = ctx.get_account(&pubkey).unwrap();
+    assert_eq!(account.lamports, 42_000);
+    assert_eq!(account.owner, owner);
+    assert_eq!(account.data, vec![1, 2, 3]);
+    assert!(!account.executable);
+    assert_eq!(account.rent_epoch, u64::MAX);
+}
+
+#[test]
+fn test_assert_deterministic() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let ix = solana_system_interface::instruction::transfer(&alice, &bob, 1_000);
+    ctx.assert_deterministic(&ix, 5).unwrap();
+
+    assert_eq!(ctx.get_balance(&alice), Some(1_000_000));
+    assert_eq!(ctx.get_balance(&bob), Some(0));
+}
+
+#[test]
+fn test_named_instructions_in_errors_and_summary() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    let other = Pubkey::new_unique();
+    ctx.create_mint(&mint, &authority, 6);
+    ctx.create_token_account(&token_account, &mint, &authority, 0);
+    ctx.create_token_account(&other, &mint, &authority, 0);
+
+    let mint_ix = spl_token::instruction::mint_to(
+        &MolluskContextHelper::token_program(),
+        &mint,
+        &token_account,
+        &authority,
+        &[],
+        100,
+    )
+    .unwrap();
+    let transfer_ix = spl_token::instruction::transfer(
+        &MolluskContextHelper::token_program(),
+        &token_account,
+        &other,
+        &authority,
+        &[],
+        1_000,
+    )
+    .unwrap();
+
+    let err = ctx
+        .transaction()
+        .add_named_instruction("mint_to", mint_ix.clone())
+        .add_named_instruction("transfer", transfer_ix)
+        .execute()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        MolluskHelperError::TransactionFailed { index: 1, .. }
+    ));
+    assert!(err.to_string().contains("'transfer'"), "{err}");
+
+    let result = ctx
+        .transaction()
+        .add_named_instruction("mint_to", mint_ix)
+        .execute()
+        .unwrap();
+    assert!(result.summary().contains("'mint_to'"));
+}
+
+#[test]
+fn test_initialize_mint2() {
+    use solana_program_option::COption;
+    use solana_program_pack::Pack;
+
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let freeze_authority = Pubkey::new_unique();
+
+    ctx.create_data_accounts(&[(
+        mint,
+        MolluskContextHelper::token_program(),
+        vec![0u8; spl_token::state::Mint::LEN],
+    )])
+    .unwrap();
+
+    ctx.initialize_mint2(&mint, &authority, Some(&freeze_authority), 9)
+        .unwrap();
+
+    let account = ctx.get_account(&mint).unwrap();
+    let state = spl_token::state::Mint::unpack(&account.data).unwrap();
+    assert!(state.is_initialized);
+    assert_eq!(state.decimals, 9);
+    assert_eq!(state.mint_authority, COption::Some(authority));
+    assert_eq!(
+        ctx.get_freeze_authority(&mint).unwrap(),
+        Some(freeze_authority)
+    );
+}
+
+#[test]
+fn test_reset_clock_keeps_accounts() {
+    let mut ctx = MolluskContextHelper::new_without_program_with_timestamp(1_000);
+
+    let alice = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+
+    ctx.warp_to_slot(1_000_000);
+    ctx.update_unix_timestamp(9_999_999);
+    assert_eq!(ctx.get_unix_timestamp(), 9_999_999);
+
+    ctx.reset_clock();
+    assert_eq!(ctx.get_unix_timestamp(), 1_000);
+    assert_eq!(ctx.get_balance(&alice), Some(1_000_000));
+}
+
+#[test]
+fn test_execute_raw_instructions() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    let charlie = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+    ctx.fund_account(&charlie, 0);
+
+    let spec = |from: &Pubkey, to: &Pubkey, lamports: u64| {
+        let ix = solana_system_interface::instruction::transfer(from, to, lamports);
+        (ix.program_id, ix.data, ix.accounts)
+    };
+
+    let result = ctx
+        .execute_raw_instructions(&[spec(&alice, &bob, 300_000), spec(&bob, &charlie, 100_000)])
+        .unwrap();
+
+    assert_eq!(result.instruction_results.len(), 2);
+    assert_eq!(ctx.get_balance(&alice), Some(700_000));
+    assert_eq!(ctx.get_balance(&bob), Some(200_000));
+    assert_eq!(ctx.get_balance(&charlie), Some(100_000));
+}
+
+#[test]
+fn test_assert_programs_invoked() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let payer = Pubkey::new_unique();
+    let wallet = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let mint_authority = Pubkey::new_unique();
+
+    ctx.fund_account(&payer, 1_000_000_000);
+    ctx.create_mint(&mint, &mint_authority, 6);
+
+    let ix =
+        MolluskContextHelper::create_associated_token_account_instruction(&payer, &wallet, &mint);
+    let result = ctx.process_instruction(&ix).unwrap();
+
+    ctx.assert_programs_invoked(
+        &result,
+        &[
+            MolluskContextHelper::associated_token_program(),
+            MolluskContextHelper::token_program(),
+            MolluskContextHelper::system_program(),
+        ],
+    )
+    .unwrap();
+
+    let err = ctx
+        .assert_programs_invoked(
+            &result,
+            &[
+                MolluskContextHelper::token_program(),
+                MolluskContextHelper::system_program(),
+            ],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&MolluskContextHelper::associated_token_program().to_string()));
+}
+
+#[test]
+fn test_create_user() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let mint_authority = Pubkey::new_unique();
+    ctx.create_mint(&mint, &mint_authority, 6);
+
+    let alice = ctx
+        .create_user("alice", 1_000_000_000, &mint, &mint_authority, 5_000)
+        .unwrap();
+    let bob = ctx
+        .create_user("bob", 1_000_000_000, &mint, &mint_authority, 0)
+        .unwrap();
+
+    assert_eq!(ctx.get_keypair_pubkey("alice").unwrap(), alice.pubkey);
+    assert_eq!(
+        alice.ata,
+        ctx.get_associated_token_address(&alice.pubkey, &mint)
+    );
+    assert_eq!(ctx.get_token_balance(&alice.ata).unwrap(), 5_000);
+    assert_eq!(ctx.get_token_balance(&bob.ata).unwrap(), 0);
+
+    ctx.transfer_tokens(&alice.ata, &bob.ata, &alice.pubkey, 2_000)
+        .unwrap();
+    assert_eq!(ctx.get_token_balance(&alice.ata).unwrap(), 3_000);
+    assert_eq!(ctx.get_token_balance(&bob.ata).unwrap(), 2_000);
+}
+
+#[test]
+fn test_sysvars_summary() {
+    let mut ctx = MolluskContextHelper::new_without_program_with_timestamp(1_234);
+    ctx.warp_to_slot(500);
+    ctx.update_unix_timestamp(5_678);
+
+    let summary = ctx.sysvars_summary();
+    assert_eq!(summary.slot, 500);
+    assert_eq!(summary.unix_timestamp, ctx.get_unix_timestamp());
+    assert_eq!(
+        summary.rent_exempt_base_lamports,
+        ctx.minimum_balance_for_rent_exemption(0)
+    );
+    assert!(summary.slots_per_epoch > 0);
+}
+
+#[test]
+fn test_assert_no_return_data() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let transfer = ctx.transfer_sol(&alice, &bob, 1_000).unwrap();
+    ctx.assert_no_return_data(&transfer).unwrap();
+
+    let mint = Pubkey::new_unique();
+    ctx.create_mint(&mint, &Pubkey::new_unique(), 6);
+    let ix = spl_token::instruction::get_account_data_size(
+        &MolluskContextHelper::token_program(),
+        &mint,
+    )
+    .unwrap();
+    let result = ctx.process_instruction(&ix).unwrap();
+    assert!(ctx.assert_no_return_data(&result).is_err());
+}
+
+#[test]
+fn test_ensure_sysvar_accounts() {
+    use solana_program_pack::Pack;
+
+    let mut ctx = MolluskContextHelper::new_without_program();
+
+    let rent_sysvar = MolluskContextHelper::rent_sysvar();
+    let clock_sysvar = solana_pubkey::pubkey!("SysvarC1ock11111111111111111111111111111111");
+    assert!(ctx.get_account(&rent_sysvar).is_some());
+    assert!(ctx.get_account(&clock_sysvar).is_some());
+
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    ctx.create_data_accounts(&[(
+        mint,
+        MolluskContextHelper::token_program(),
+        vec![0u8; spl_token::state::Mint::LEN],
+    )])
+    .unwrap();
+
+    let ix = spl_token::instruction::initialize_mint(
+        &MolluskContextHelper::token_program(),
+        &mint,
+        &authority,
+        None,
+        6,
+    )
+    .unwrap();
+    assert!(ix.accounts.iter().any(|meta| meta.pubkey == rent_sysvar));
+    ctx.process_instruction(&ix).unwrap();
+
+    let state = spl_token::state::Mint::unpack(&ctx.get_account(&mint).unwrap().data).unwrap();
+    assert_eq!(state.decimals, 6);
+
+    let stored_slot = |ctx: &MolluskContextHelper| {
+        let (slot, _, _, _, _): (u64, i64, u64, u64, i64) =
+            bincode::deserialize(&ctx.get_account(&clock_sysvar).unwrap().data).unwrap();
+        slot
+    };
+    assert_eq!(stored_slot(&ctx), 0);
+
+    ctx.checkpoint("slot-0").unwrap();
+    ctx.warp_to_slot(100);
+    assert_eq!(stored_slot(&ctx), 100);
+
+    ctx.restore_checkpoint("slot-0").unwrap();
+    assert_eq!(stored_slot(&ctx), 100);
+}
+
+#[test]
+fn test_account_totals() {
+    let ctx = MolluskContextHelper::new_without_program();
+    assert_eq!(ctx.total_account_data_bytes(), 0);
+    assert_eq!(ctx.total_lamports(), 0);
+
+    let owner = Pubkey::new_unique();
+    ctx.add_account(&Pubkey::new_unique(), Account::new(1_000, 10, &owner));
+    ctx.add_account(&Pubkey::new_unique(), Account::new(2_000, 32, &owner));
+    ctx.fund_account(&Pubkey::new_unique(), u64::MAX);
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+    ctx.transfer_sol(&alice, &bob, 1_000).unwrap();
+
+    assert_eq!(ctx.total_account_data_bytes(), 42);
+    assert_eq!(ctx.total_lamports(), 1_003_000 + u128::from(u64::MAX));
+}
+
+#[test]
+fn test_process_instruction_expect_abort() {
+    let program_id = Pubkey::new_unique();
+    let ctx = MolluskContextHelper::new(&program_id, TEST_PROGRAM_ELF);
+
+    let abort = Instruction::new_with_bytes(program_id, &[2], vec![]);
+    ctx.process_instruction_expect_abort(&abort).unwrap();
+
+    let log_only = Instruction::new_with_bytes(program_id, &[0, b'h', b'i'], vec![]);
+    assert!(ctx.process_instruction_expect_abort(&log_only).is_err());
+}
+
+#[test]
+fn test_process_instruction_expect_abort_rejects_other_outcomes() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let memo_ok = Instruction::new_with_bytes(MolluskContextHelper::memo_program(), b"hi", vec![]);
+    let err = ctx.process_instruction_expect_abort(&memo_ok).unwrap_err();
+    assert!(err.to_string().contains("succeeded"), "{err}");
+
+    let memo_invalid =
+        Instruction::new_with_bytes(MolluskContextHelper::memo_program(), &[0xff, 0xfe], vec![]);
+    let err = ctx
+        .process_instruction_expect_abort(&memo_invalid)
+        .unwrap_err();
+    assert!(err.to_string().contains("returned an error"), "{err}");
+}
+
+#[test]
+fn test_create_token_accounts_for_mint() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    ctx.create_mint(&mint, &Pubkey::new_unique(), 6);
+
+    let holders = [
+        (Pubkey::new_unique(), 100),
+        (Pubkey::new_unique(), 250),
+        (Pubkey::new_unique(), 0),
+    ];
+    let accounts = ctx.create_token_accounts_for_mint(&mint, &holders);
+
+    assert_eq!(accounts.len(), holders.len());
+    for (account, (_, amount)) in accounts.iter().zip(&holders) {
+        assert_eq!(ctx.get_token_balance(account).unwrap(), *amount);
+    }
+}
+
+#[test]
+fn test_assert_signed_by() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    ctx.store_keypair("payer", Keypair::new()).unwrap();
+    ctx.store_keypair("bystander", Keypair::new()).unwrap();
+    let payer = ctx.get_keypair_pubkey("payer").unwrap();
+
+    let ix = solana_system_interface::instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+    let message =
+        solana_message::v0::Message::try_compile(&payer, &[ix], &[], solana_hash::Hash::default())
+            .unwrap();
+    let tx = ctx
+        .sign_versioned_message(VersionedMessage::V0(message), &["payer"])
+        .unwrap();
+
+    ctx.assert_signed_by(&tx, "payer").unwrap();
+    assert!(ctx.assert_signed_by(&tx, "bystander").is_err());
+}
+
+#[test]
+fn test_with_payer_and_fee() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let payer = Pubkey::new_unique();
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&payer, 100_000);
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    ctx.transaction()
+        .add_instruction(solana_system_interface::instruction::transfer(
+            &alice, &bob, 1_000,
+        ))
+        .with_payer_and_fee(&payer, 7_500)
+        .execute()
+        .unwrap();
+    assert_eq!(ctx.get_balance(&payer), Some(92_500));
+    assert_eq!(ctx.get_balance(&bob), Some(1_000));
+
+    let result = ctx
+        .transaction()
+        .add_instruction(solana_system_interface::instruction::transfer(
+            &alice, &bob, 10_000_000,
+        ))
+        .with_payer_and_fee(&payer, 7_500)
+        .execute();
+    assert!(result.is_err());
+    assert_eq!(ctx.get_balance(&payer), Some(92_500));
+}
+
+#[test]
+fn test_serialize_and_deserialize_state() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let bytes = ctx.serialize_state();
+
+    ctx.transfer_sol(&alice, &bob, 400_000).unwrap();
+    let carol = Pubkey::new_unique();
+    ctx.fund_account(&carol, 5);
+
+    ctx.deserialize_state(&bytes).unwrap();
+    assert_eq!(ctx.get_balance(&alice), Some(1_000_000));
+    assert_eq!(ctx.get_balance(&bob), Some(0));
+    assert!(ctx.get_account(&carol).is_none());
+
+    assert!(ctx.deserialize_state(&[1, 2, 3]).is_err());
+}
+
+#[test]
+fn test_burn_checked() {
+    use solana_program_pack::Pack;
+
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    ctx.create_mint(&mint, &owner, 6);
+    ctx.create_token_account(&token_account, &mint, &owner, 0);
+    ctx.mint_to(&mint, &token_account, &owner, 1_000).unwrap();
+
+    ctx.burn_checked(&mint, &token_account, &owner, 400, 6)
+        .unwrap();
+    assert_eq!(ctx.get_token_balance(&token_account).unwrap(), 600);
+    let supply = spl_token::state::Mint::unpack(&ctx.get_account(&mint).unwrap().data)
+        .unwrap()
+        .supply;
+    assert_eq!(supply, 600);
+
+    assert!(ctx
+        .burn_checked(&mint, &token_account, &owner, 100, 9)
+        .is_err());
+    assert_eq!(ctx.get_token_balance(&token_account).unwrap(), 600);
+}
+
+#[test]
+fn test_assert_owner_changed() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let payer = Pubkey::new_unique();
+    let new_account = Pubkey::new_unique();
+    let target_program = MolluskContextHelper::token_program();
+    ctx.fund_account(&payer, 1_000_000_000);
+
+    let lamports = ctx.minimum_balance_for_rent_exemption(16);
+    let ix = solana_system_interface::instruction::create_account(
+        &payer,
+        &new_account,
+        lamports,
+        16,
+        &target_program,
+    );
+    let before = ctx.get_account(&new_account).unwrap_or_default();
+    let result = ctx.process_instruction(&ix).unwrap();
+
+    let system_program = MolluskContextHelper::system_program();
+    ctx.assert_owner_changed(
+        &new_account,
+        &before,
+        system_program,
+        target_program,
+        &result,
+    )
+    .unwrap();
+    assert!(ctx
+        .assert_owner_changed(
+            &new_account,
+            &before,
+            system_program,
+            Pubkey::new_unique(),
+            &result
+        )
+        .is_err());
+
+    let after = ctx.get_account(&new_account).unwrap();
+    assert!(ctx
+        .assert_owner_changed(
+            &new_account,
+            &after,
+            system_program,
+            target_program,
+            &result
+        )
+        .is_err());
+}
+
+#[test]
+fn test_build_max_size_transfer_chain() {
+    let payer = Pubkey::new_unique();
+
+    let chain = MolluskContextHelper::build_max_size_transfer_chain(&payer, 1_000);
+    assert!(!chain.is_empty());
+
+    let (size, limit, remaining) =
+        MolluskContextHelper::build_versioned_tx_size(&payer, &chain, &[]);
+    assert!(size <= limit);
+    assert!(remaining < 64, "{remaining} bytes left unused");
+
+    let mut overfull = chain.clone();
+    overfull.push(solana_system_interface::instruction::transfer(
+        &payer,
+        &Pubkey::new_unique(),
+        1,
+    ));
+    assert!(MolluskContextHelper::assert_tx_fits(&payer, &overfull, &[]).is_err());
+
+    let capped = MolluskContextHelper::build_max_size_transfer_chain(&payer, 3);
+    assert_eq!(capped.len(), 3);
+}
+
+#[test]
+fn test_process_instruction_with_changes() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let ix = solana_system_interface::instruction::transfer(&alice, &bob, 250_000);
+    let (result, diffs) = ctx.process_instruction_with_changes(&ix).unwrap();
+    assert!(result.program_result.is_ok());
+
+    assert_eq!(diffs.len(), 2);
+    let alice_diff = diffs.iter().find(|d| d.pubkey == alice).unwrap();
+    let bob_diff = diffs.iter().find(|d| d.pubkey == bob).unwrap();
+    assert_eq!(alice_diff.lamport_delta(), -250_000);
+    assert_eq!(bob_diff.lamport_delta(), 250_000);
+}
+
+#[test]
+fn test_create_associated_token_account_fixture() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let wallet = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    ctx.create_mint(&mint, &wallet, 6);
+
+    let ata = ctx.create_associated_token_account_fixture(&wallet, &mint, &wallet, 750);
+
+    assert_eq!(ata, ctx.get_associated_token_address(&wallet, &mint));
+    assert_eq!(ctx.get_token_balance(&ata).unwrap(), 750);
+}
+
+#[test]
+fn test_burn_freeze_and_thaw() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let freeze_authority = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+
+    ctx.create_mint_with_freeze_authority(&mint, &authority, &freeze_authority, 6);
+    ctx.create_token_account(&source, &mint, &authority, 0);
+    ctx.create_token_account(&destination, &mint, &authority, 0);
+    ctx.mint_to(&mint, &source, &authority, 1_000).unwrap();
+
+    ctx.burn_tokens(&mint, &source, &authority, 100).unwrap();
+    assert_eq!(ctx.get_token_balance(&source).unwrap(), 900);
+
+    ctx.freeze_token_account(&source, &mint, &freeze_authority)
+        .unwrap();
+    assert_eq!(ctx.get_token_balance(&source).unwrap(), 900);
+    assert!(ctx
+        .transfer_tokens(&source, &destination, &authority, 100)
+        .is_err());
+
+    ctx.thaw_token_account(&source, &mint, &freeze_authority)
+        .unwrap();
+    ctx.transfer_tokens(&source, &destination, &authority, 100)
+        .unwrap();
+    assert_eq!(ctx.get_token_balance(&destination).unwrap(), 100);
+
+    let unfreezable = Pubkey::new_unique();
+    let holder = Pubkey::new_unique();
+    ctx.create_mint(&unfreezable, &authority, 6);
+    ctx.create_token_account(&holder, &unfreezable, &authority, 0);
+    assert!(ctx
+        .freeze_token_account(&holder, &unfreezable, &freeze_authority)
+        .is_err());
+}
+
+#[test]
+fn test_assert_non_trivial() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let result = ctx
+        .transaction()
+        .add_instruction(solana_system_interface::instruction::transfer(
+            &alice, &bob, 1_000,
+        ))
+        .execute()
+        .unwrap();
+    ctx.assert_non_trivial(&result).unwrap();
+
+    let empty = ctx.transaction().execute().unwrap();
+    assert!(ctx.assert_non_trivial(&empty).is_err());
+}
+
+#[test]
+fn test_create_mint_with_options() {
+    use solana_program_option::COption;
+    use solana_program_pack::Pack;
+
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let freeze_authority = Pubkey::new_unique();
+    ctx.create_mint_with_options(&mint, &authority, 9, Some(&freeze_authority), 1_000_000);
+
+    let state = spl_token::state::Mint::unpack(&ctx.get_account(&mint).unwrap().data).unwrap();
+    assert_eq!(state.supply, 1_000_000);
+    assert_eq!(state.decimals, 9);
+    assert_eq!(state.freeze_authority, COption::Some(freeze_authority));
+
+    let plain = Pubkey::new_unique();
+    ctx.create_mint(&plain, &authority, 6);
+    let state = spl_token::state::Mint::unpack(&ctx.get_account(&plain).unwrap().data).unwrap();
+    assert_eq!(state.supply, 0);
+    assert_eq!(state.freeze_authority, COption::None);
+}
+
+#[test]
+fn test_get_mint_info() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let freeze_authority = Pubkey::new_unique();
+    ctx.create_mint_with_options(&mint, &authority, 6, Some(&freeze_authority), 42);
+
+    let info = ctx.get_mint_info(&mint).unwrap();
+    assert_eq!(info.decimals, 6);
+    assert_eq!(info.supply, 42);
+    assert_eq!(info.mint_authority, Some(authority));
+    assert_eq!(info.freeze_authority, Some(freeze_authority));
+    assert!(info.is_initialized);
+
+    assert!(matches!(
+        ctx.get_mint_info(&Pubkey::new_unique()),
+        Err(MolluskHelperError::AccountNotFound(_))
+    ));
+
+    let not_a_mint = Pubkey::new_unique();
+    ctx.add_account(
+        &not_a_mint,
+        Account {
+            lamports: 1_000_000,
+            data: vec![0; 10],
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    assert!(matches!(
+        ctx.get_mint_info(&not_a_mint),
+        Err(MolluskHelperError::ProgramError(_))
+    ));
+}
+
+#[test]
+fn test_native_token_account_with_explicit_reserve() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let owner = Pubkey::new_unique();
+    let wsol = Pubkey::new_unique();
+    let lamports = 3_000_000;
+    let reserve = 1_234_567;
+
+    ctx.create_native_token_account_with_reserve(&wsol, &owner, lamports, reserve);
+
+    assert_eq!(ctx.get_balance(&wsol), Some(lamports));
+    assert_eq!(
+        ctx.get_wrapped_sol_spendable(&wsol).unwrap(),
+        lamports - reserve
+    );
+    assert_eq!(ctx.get_token_balance(&wsol).unwrap(), lamports - reserve);
+}
+
+#[test]
+fn test_get_token_account_info() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let freeze_authority = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    ctx.create_mint_with_freeze_authority(&mint, &owner, &freeze_authority, 6);
+    ctx.create_token_account(&token_account, &mint, &owner, 500);
+
+    let info = ctx.get_token_account_info(&token_account).unwrap();
+    assert_eq!(info.mint, mint);
+    assert_eq!(info.owner, owner);
+    assert_eq!(info.amount, 500);
+    assert_eq!(info.delegate, None);
+    assert_eq!(info.state, TokenAccountState::Initialized);
+    assert_eq!(info.is_native, None);
+    assert_eq!(info.close_authority, None);
+
+    ctx.freeze_token_account(&token_account, &mint, &freeze_authority)
+        .unwrap();
+    assert_eq!(
+        ctx.get_token_account_info(&token_account).unwrap().state,
+        TokenAccountState::Frozen
+    );
+
+    assert!(matches!(
+        ctx.get_token_account_info(&Pubkey::new_unique()),
+        Err(MolluskHelperError::AccountNotFound(_))
+    ));
+}
+
+#[test]
+fn test_transfer_sol_checked_rejects_overdraw() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let err = ctx
+        .transfer_sol_checked(&alice, &bob, 2_000_000)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        MolluskHelperError::InsufficientFunds {
+            have: 1_000_000,
+            need: 2_000_000
+        }
+    ));
+    assert_eq!(ctx.get_balance(&alice), Some(1_000_000));
+
+    ctx.transfer_sol_checked(&alice, &bob, 400_000).unwrap();
+    assert_eq!(ctx.get_balance(&alice), Some(600_000));
+    assert_eq!(ctx.get_balance(&bob), Some(400_000));
+}
+
+#[test]
+fn test_transaction_result_to_json() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let result = ctx
+        .transaction()
+        .add_instruction(solana_system_interface::instruction::transfer(
+            &alice, &bob, 100,
+        ))
+        .add_instruction(solana_system_interface::instruction::transfer(
+            &alice, &bob, 200,
+        ))
+        .execute()
+        .unwrap();
+
+    let json: serde_json::Value = serde_json::from_str(&result.to_json()).unwrap();
+    assert_eq!(json["success"], true);
+    assert_eq!(json["instruction_count"], 2);
+    assert_eq!(json["instructions"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        json["total_compute_units"].as_u64().unwrap(),
+        result.total_compute_units
+    );
+    assert!(json["instructions"][0]["logs"].is_array());
+}
+
+#[test]
+fn test_token_2022_mint_and_transfer() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+
+    ctx.create_mint_2022(&mint, &authority, 6);
+    ctx.create_token_account_2022(&source, &mint, &authority, 0);
+    ctx.create_token_account_2022(&destination, &mint, &recipient, 0);
+
+    for pubkey in [mint, source, destination] {
+        let account = ctx.get_account(&pubkey).unwrap();
+        assert_eq!(
+            account.lamports,
+            ctx.minimum_balance_for_rent_exemption(account.data.len())
+        );
+    }
+
+    ctx.mint_to_2022(&mint, &source, &authority, 1_000).unwrap();
+    ctx.transfer_tokens_2022(&source, &mint, &destination, &authority, 400, 6)
+        .unwrap();
+
+    assert_eq!(ctx.get_token_balance(&source).unwrap(), 600);
+    assert_eq!(ctx.get_token_balance(&destination).unwrap(), 400);
+    assert_eq!(
+        ctx.get_account(&destination).unwrap().owner,
+        TOKEN_2022_PROGRAM_ID
+    );
+
+    assert!(ctx
+        .transfer_tokens(&source, &destination, &authority, 100)
+        .is_err());
+    assert_eq!(ctx.get_token_balance(&source).unwrap(), 600);
+}
+
+#[test]
+fn test_approve_and_revoke_delegate() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let delegate = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+
+    ctx.create_mint(&mint, &owner, 6);
+    ctx.create_token_account(&source, &mint, &owner, 1_000);
+    ctx.create_token_account(&destination, &mint, &Pubkey::new_unique(), 0);
+
+    ctx.approve_delegate(&source, &delegate, &owner, 300)
+        .unwrap();
+    let info = ctx.get_token_account_info(&source).unwrap();
+    assert_eq!(info.delegate, Some(delegate));
+    assert_eq!(info.delegated_amount, 300);
+
+    ctx.transfer_tokens(&source, &destination, &delegate, 120)
+        .unwrap();
+    let info = ctx.get_token_account_info(&source).unwrap();
+    assert_eq!(info.amount, 880);
+    assert_eq!(info.delegated_amount, 180);
+    assert_eq!(ctx.get_token_balance(&destination).unwrap(), 120);
+
+    ctx.revoke_delegate(&source, &owner).unwrap();
+    let info = ctx.get_token_account_info(&source).unwrap();
+    assert_eq!(info.delegate, None);
+    assert_eq!(info.delegated_amount, 0);
+    assert!(ctx
+        .transfer_tokens(&source, &destination, &delegate, 10)
+        .is_err());
+}
+
+#[test]
+fn test_assert_balances_aggregates_mismatches() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    let carol = Pubkey::new_unique();
+    ctx.fund_account(&alice, 100);
+    ctx.fund_account(&bob, 200);
+    ctx.fund_account(&carol, 300);
+
+    ctx.assert_balances(&[(alice, 100), (bob, 200), (carol, 300)])
+        .unwrap();
+
+    let err = ctx
+        .assert_balances(&[(alice, 100), (bob, 250), (carol, 300)])
+        .unwrap_err();
+    match err {
+        MolluskHelperError::AssertionFailed(message) => {
+            assert!(message.contains(&bob.to_string()));
+            assert!(message.contains("expected 250, got 200"));
+            assert!(!message.contains(&alice.to_string()));
+            assert!(!message.contains(&carol.to_string()));
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+}
+
+#[test]
+fn test_close_token_account_requires_zero_balance() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+
+    ctx.create_mint(&mint, &owner, 6);
+    ctx.create_token_account(&token_account, &mint, &owner, 0);
+    ctx.mint_to(&mint, &token_account, &owner, 500).unwrap();
+    ctx.fund_account(&destination, 1_000);
+
+    assert!(ctx
+        .close_token_account(&token_account, &destination, &owner)
+        .is_err());
+    assert_eq!(ctx.get_token_balance(&token_account).unwrap(), 500);
+    assert_eq!(ctx.get_balance(&destination), Some(1_000));
+
+    ctx.burn_tokens(&mint, &token_account, &owner, 500).unwrap();
+    let prior_lamports = ctx.get_balance(&token_account).unwrap();
+
+    let (_, reclaimed) = ctx
+        .close_token_account(&token_account, &destination, &owner)
+        .unwrap();
+    assert_eq!(reclaimed, prior_lamports);
+    assert_eq!(ctx.get_balance(&destination), Some(1_000 + prior_lamports));
+    ctx.assert_account_closed(&token_account).unwrap();
+}
+
+#[test]
+fn test_warp_to_next_epoch() {
+    let mut ctx = MolluskContextHelper::new_without_program();
+    ctx.warp_to_slot(1_000);
+
+    let before = ctx.sysvars_summary();
+    let epoch = ctx.warp_to_next_epoch();
+    let after = ctx.sysvars_summary();
+
+    assert_eq!(epoch, before.epoch + 1);
+    assert_eq!(after.epoch, epoch);
+    assert!(after.slot > before.slot);
+
+    let mut fork = ctx.fork();
+    fork.warp_to_slot(after.slot - 1);
+    assert_eq!(fork.sysvars_summary().epoch, before.epoch);
+}
+
+#[test]
+fn test_restore_checkpoint_between_scenarios() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+    ctx.checkpoint("base").unwrap();
+
+    for amount in [100_000, 250_000, 999_999] {
+        ctx.transfer_sol(&alice, &bob, amount).unwrap();
+        assert_eq!(ctx.get_balance(&bob), Some(amount));
+
+        ctx.restore_checkpoint("base").unwrap();
+        assert_eq!(ctx.get_balance(&alice), Some(1_000_000));
+        assert_eq!(ctx.get_balance(&bob), Some(0));
+    }
+
+    let scratch = Pubkey::new_unique();
+    ctx.fund_account(&scratch, 5);
+    ctx.restore_checkpoint("base").unwrap();
+    assert_eq!(ctx.get_account(&scratch), None);
+
+    assert!(matches!(
+        ctx.restore_checkpoint("unknown"),
+        Err(MolluskHelperError::CheckpointNotFound(_))
+    ));
+}
+
+#[test]
+fn test_freeze_clock_pins_timestamp() {
+    let mut ctx = MolluskContextHelper::new_without_program();
+    ctx.freeze_clock(1_700_000_000);
+
+    ctx.warp_to_slot(100);
+    let first = ctx.get_unix_timestamp();
+    ctx.warp_to_next_epoch();
+    let second = ctx.get_unix_timestamp();
+
+    assert_eq!(first, 1_700_000_000);
+    assert_eq!(first, second);
+
+    ctx.reset_clock();
+    assert_eq!(ctx.get_unix_timestamp(), 1_700_000_000);
+    assert_eq!(ctx.fork().get_unix_timestamp(), 1_700_000_000);
+}
+
+#[test]
+fn test_all_accounts_and_accounts_owned_by() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let funded: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+    for (i, pubkey) in funded.iter().enumerate() {
+        ctx.fund_account(pubkey, 1_000 * (i as u64 + 1));
+    }
+
+    let mint = Pubkey::new_unique();
+    ctx.create_mint(&mint, &Pubkey::new_unique(), 6);
+    ctx.transfer_sol(&funded[2], &funded[0], 100).unwrap();
+
+    let all = ctx.all_accounts();
+    assert_eq!(all.len(), 4);
+    assert!(funded
+        .iter()
+        .all(|pubkey| all.iter().any(|(key, _)| key == pubkey)));
+    assert!(all.iter().any(|(key, _)| *key == mint));
+
+    let system_owned = ctx.accounts_owned_by(&MolluskContextHelper::system_program());
+    assert_eq!(system_owned.len(), 3);
+    assert!(system_owned
+        .iter()
+        .all(|(key, account)| funded.contains(key)
+            && account.owner == MolluskContextHelper::system_program()));
+
+    let lamports: u128 = all.iter().map(|(_, a)| u128::from(a.lamports)).sum();
+    assert_eq!(lamports, ctx.total_lamports());
+}
+
+#[test]
+fn test_non_rent_exempt_accounts() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let owner = Pubkey::new_unique();
+    let delinquent = Pubkey::new_unique();
+    let exempt = Pubkey::new_unique();
+    let required = ctx.minimum_balance_for_rent_exemption(100);
+
+    ctx.add_account(
+        &delinquent,
+        Account {
+            lamports: required - 1,
+            data: vec![0; 100],
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    ctx.add_account(
+        &exempt,
+        Account {
+            lamports: required,
+            data: vec![0; 100],
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 10_000_000);
+    ctx.fund_account(&bob, 1_000_000);
+    ctx.transfer_sol(&alice, &bob, 1_000).unwrap();
+
+    assert_eq!(
+        ctx.non_rent_exempt_accounts(),
+        vec![(delinquent, required - 1, required)]
+    );
+}
+
+#[test]
+fn test_remove_account() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    ctx.create_mint(&mint, &authority, 6);
+    ctx.create_token_account(&token_account, &mint, &authority, 0);
+    ctx.mint_to(&mint, &token_account, &authority, 10).unwrap();
+
+    assert!(ctx.account_exists(&mint));
+    let removed = ctx.remove_account(&mint).unwrap();
+    assert_eq!(removed.owner, MolluskContextHelper::token_program());
+    assert!(!ctx.account_exists(&mint));
+    assert_eq!(ctx.get_account(&mint), None);
+    assert_eq!(ctx.remove_account(&mint), None);
+
+    assert!(ctx.mint_to(&mint, &token_account, &authority, 10).is_err());
+    assert_eq!(ctx.get_token_balance(&token_account).unwrap(), 10);
+}
+
+#[test]
+fn test_create_and_initialize_is_atomic() {
+    use solana_program_pack::Pack;
+
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let payer = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let token_program = MolluskContextHelper::token_program();
+    let lamports = ctx.minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN);
+    ctx.fund_account(&payer, 10_000_000_000);
+
+    let create_ix = solana_system_interface::instruction::create_account(
+        &payer,
+        &mint,
+        lamports,
+        spl_token::state::Mint::LEN as u64,
+        &token_program,
+    );
+    let init_ix =
+        spl_token::instruction::initialize_mint2(&token_program, &mint, &authority, None, 6)
+            .unwrap();
+
+    let result = ctx.create_and_initialize(create_ix, init_ix).unwrap();
+    assert_eq!(result.instruction_results.len(), 2);
+
+    let info = ctx.get_mint_info(&mint).unwrap();
+    assert!(info.is_initialized);
+    assert_eq!(info.decimals, 6);
+    assert_eq!(info.mint_authority, Some(authority));
+
+    let other = Pubkey::new_unique();
+    let create_ix = solana_system_interface::instruction::create_account(
+        &payer,
+        &other,
+        lamports,
+        1,
+        &token_program,
+    );
+    let init_ix =
+        spl_token::instruction::initialize_mint2(&token_program, &other, &authority, None, 6)
+            .unwrap();
+    let payer_before = ctx.get_balance(&payer);
+
+    assert!(ctx.create_and_initialize(create_ix, init_ix).is_err());
+    assert!(!ctx.account_exists(&other));
+    assert_eq!(ctx.get_balance(&payer), payer_before);
+}
+
+#[test]
+fn test_create_keypair_from_seed_is_deterministic() {
+    let ctx = MolluskContextHelper::new_without_program();
+    let other = MolluskContextHelper::new_without_program();
+
+    let seed = [42u8; 32];
+    let first = ctx.create_keypair_from_seed("alice", &seed).unwrap();
+    let second = other.create_keypair_from_seed("alice", &seed).unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(ctx.get_keypair_pubkey("alice").unwrap(), first);
+    assert_ne!(
+        ctx.create_keypair_from_seed("bob", &[43u8; 32]).unwrap(),
+        first
+    );
+
+    assert!(matches!(
+        ctx.create_keypair_from_seed("short", &[1u8; 8]),
+        Err(MolluskHelperError::KeypairDerivationFailed(_))
+    ));
+}
+
+#[test]
+fn test_with_priority_fee_charges_for_compute() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let payer = Pubkey::new_unique();
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&payer, 1_000_000);
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let result = ctx
+        .transaction()
+        .add_instruction(solana_system_interface::instruction::transfer(
+            &alice, &bob, 1_000,
+        ))
+        .with_payer_and_fee(&payer, 5_000)
+        .with_priority_fee(2_000_000)
+        .execute()
+        .unwrap();
+
+    assert!(result.total_compute_units > 0);
+    let expected_fee = 5_000 + 2 * result.total_compute_units;
+    assert_eq!(ctx.get_balance(&payer), Some(1_000_000 - expected_fee));
+}
+
+#[test]
+fn test_find_pda_and_create_pda_address() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let program_id = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let seeds: &[&[u8]] = &[b"vault", user.as_ref()];
+
+    let (pda, bump) = ctx.find_pda(seeds, &program_id);
+    assert_eq!(
+        ctx.create_pda_address(seeds, bump, &program_id).unwrap(),
+        pda
+    );
+
+    ctx.fund_account(&pda, 0);
+    ctx.fund_account(&user, 1_000_000);
+    ctx.transfer_sol(&user, &pda, 250_000).unwrap();
+    assert_eq!(ctx.get_balance(&pda), Some(250_000));
+
+    let oversized = [0u8; 33];
+    assert!(matches!(
+        ctx.create_pda_address(&[&oversized], bump, &program_id),
+        Err(MolluskHelperError::InvalidSeeds(_))
+    ));
+}
+
+#[test]
+fn test_assert_no_accounts_created() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let before = ctx.accounts_snapshot();
+    ctx.transfer_sol(&alice, &bob, 1_000).unwrap();
+    ctx.assert_no_accounts_created(&before).unwrap();
+
+    let carol = Pubkey::new_unique();
+    ctx.fund_account(&carol, 10);
+    let err = ctx.assert_no_accounts_created(&before).unwrap_err();
+    assert!(err.to_string().contains(&carol.to_string()));
+}
+
+#[test]
+fn test_process_instruction_with_signers() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let first = Pubkey::new_unique();
+    let second = Pubkey::new_unique();
+    ctx.fund_account(&first, 1_000_000);
+    ctx.fund_account(&second, 1_000_000);
+
+    let ix = Instruction::new_with_bytes(
+        MEMO_PROGRAM_ID,
+        b"two signers",
+        vec![
+            AccountMeta::new_readonly(first, false),
+            AccountMeta::new_readonly(second, false),
+        ],
+    );
+
+    assert!(ctx.process_instruction(&ix).is_err());
+    assert!(ctx.process_instruction_with_signers(&ix, &[first]).is_err());
+    ctx.process_instruction_with_signers(&ix, &[first, second])
+        .unwrap();
+}
+
+#[test]
+fn test_with_compute_unit_limit() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+    let transfer = solana_system_interface::instruction::transfer(&alice, &bob, 1_000);
+
+    let needed = ctx
+        .transaction()
+        .add_instruction(transfer.clone())
+        .dry_run()
+        .total_compute_units;
+    assert!(needed > 0);
+
+    let result = ctx
+        .transaction()
+        .add_instruction(transfer.clone())
+        .with_compute_unit_limit(needed as u32 - 1)
+        .execute();
+    assert!(result.is_err());
+    assert_eq!(ctx.get_balance(&bob), Some(0));
+
+    let result = ctx
+        .transaction()
+        .add_instruction(transfer)
+        .with_compute_unit_limit(needed as u32)
+        .execute()
+        .unwrap();
+    assert_eq!(result.instruction_results.len(), 1);
+    assert_eq!(result.total_compute_units, needed);
+    assert_eq!(ctx.get_balance(&bob), Some(1_000));
+}
+
+#[test]
+fn test_compute_unit_limit_raises_default_budget() {
+    let program_id = Pubkey::new_unique();
+    let ctx = MolluskContextHelper::new(&program_id, TEST_PROGRAM_ELF);
+
+    let mut data = vec![4u8];
+    data.extend_from_slice(&150_000u32.to_le_bytes());
+    let burn = Instruction::new_with_bytes(program_id, &data, vec![]);
+
+    let (result, logs) = ctx
+        .transaction()
+        .add_instruction(burn.clone())
+        .with_compute_unit_limit(200_000)
+        .execute_with_logs();
+    assert!(matches!(
+        result,
+        Err(MolluskHelperError::TransactionFailed {
+            index: 0,
+            error: InstructionError::ProgramFailedToComplete,
+            ..
+        })
+    ));
+    assert!(logs
+        .concat()
+        .iter()
+        .any(|line| line.contains("exceeded CUs meter")));
+
+    let result = ctx
+        .transaction()
+        .add_instruction(burn.clone())
+        .with_compute_unit_price(1)
+        .execute()
+        .unwrap();
+    assert!(result.total_compute_units > 300_000);
+
+    let builder = ctx
+        .transaction()
+        .add_instruction(burn.clone())
+        .with_compute_unit_limit(400_000)
+        .with_compute_unit_price(1);
+    let budget_instructions = builder.compute_budget_instructions();
+    assert_eq!(budget_instructions.len(), 2);
+    assert!(budget_instructions
+        .iter()
+        .all(|ix| ix.program_id == COMPUTE_BUDGET_PROGRAM_ID));
+    assert_eq!(budget_instructions[0].data, [2, 0x80, 0x1a, 0x06, 0x00]);
+    assert_eq!(budget_instructions[1].data, [3, 1, 0, 0, 0, 0, 0, 0, 0]);
+
+    let result = builder.execute().unwrap();
+    assert_eq!(result.instruction_results.len(), 1);
+    assert!(result.total_compute_units > 300_000);
+    assert!(result.total_compute_units <= 400_000);
+
+    let result = ctx
+        .transaction()
+        .add_instruction(burn.clone())
+        .add_instruction(burn)
+        .with_compute_unit_limit(400_000)
+        .execute_allow_failures();
+    assert_eq!(result.failed_at(), Some(1));
+    assert_eq!(result.total_compute_units, 400_000);
+}
+
+#[test]
+fn test_compute_units_per_instruction() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let result = ctx
+        .transaction()
+        .add_instruction(solana_system_interface::instruction::transfer(
+            &alice, &bob, 100,
+        ))
+        .add_instruction(Instruction::new_with_bytes(
+            MEMO_PROGRAM_ID,
+            b"a memo that costs more than a transfer",
+            vec![],
+        ))
+        .execute()
+        .unwrap();
+
+    let per_instruction = result.compute_units_per_instruction();
+    assert_eq!(per_instruction.len(), 2);
+    assert_eq!(
+        per_instruction.iter().sum::<u64>(),
+        result.total_compute_units
+    );
+    assert_eq!(
+        result.max_instruction_compute_units(),
+        per_instruction.iter().copied().max()
+    );
+
+    let empty = ctx.transaction().execute().unwrap();
+    assert!(empty.compute_units_per_instruction().is_empty());
+    assert_eq!(empty.max_instruction_compute_units(), None);
+}
+
+#[test]
+fn test_assert_log_count() {
+    let program_id = Pubkey::new_unique();
+    let ctx = MolluskContextHelper::new(&program_id, TEST_PROGRAM_ELF);
+
+    let mut data = vec![0u8];
+    data.extend_from_slice(b"hello");
+    let ix = Instruction::new_with_bytes(program_id, &data, vec![]);
+
+    let result = ctx
+        .transaction()
+        .add_instruction(ix.clone())
+        .execute()
+        .unwrap();
+    result.assert_log_contains("Program log: hello").unwrap();
+    result.assert_log_count(6).unwrap();
+    assert!(matches!(
+        result.assert_log_count(5),
+        Err(MolluskHelperError::LogAssertionFailed(_))
+    ));
+
+    let result = ctx
+        .transaction()
+        .add_instruction(ix.clone())
+        .add_instruction(ix)
+        .execute()
+        .unwrap();
+    result.assert_log_count(12).unwrap();
+}
+
+#[test]
+fn test_create_executable_account() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let loader = mollusk_svm::program::loader_keys::LOADER_V2;
+    let program = Pubkey::new_unique();
+    let data_account = Pubkey::new_unique();
+
+    ctx.create_executable_account(
+        &program,
+        &loader,
+        mollusk_svm_programs_memo::memo::ELF.to_vec(),
+    );
+    ctx.add_program_account(&data_account, &loader, vec![1, 2, 3]);
+
+    assert!(ctx.is_account_executable(&program));
+    assert_eq!(ctx.get_account(&program).unwrap().owner, loader);
+    assert!(!ctx.is_account_executable(&data_account));
+    assert!(!ctx.is_account_executable(&Pubkey::new_unique()));
+}
+
+#[test]
+fn test_process_instruction_with_logs_and_transaction_logs() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let ix = Instruction::new_with_bytes(MEMO_PROGRAM_ID, b"first memo", vec![]);
+    let (result, logs) = ctx.process_instruction_with_logs(&ix).unwrap();
+    assert!(result.program_result.is_ok());
+    assert!(logs.iter().any(|line| line.contains("first memo")));
+
+    let result = ctx
+        .transaction()
+        .add_instruction(ix)
+        .add_instruction(Instruction::new_with_bytes(
+            MEMO_PROGRAM_ID,
+            b"second memo",
+            vec![],
+        ))
+        .execute()
+        .unwrap();
+
+    let logs = result.logs();
+    let first = logs.iter().position(|l| l.contains("first memo")).unwrap();
+    let second = logs.iter().position(|l| l.contains("second memo")).unwrap();
+    assert!(first < second);
+    assert_eq!(
+        logs.len(),
+        result.instruction_logs.iter().map(Vec::len).sum::<usize>()
+    );
+}
+
+#[test]
+fn test_transaction_log_assertions() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let result = ctx
+        .transaction()
+        .add_instruction(Instruction::new_with_bytes(
+            MEMO_PROGRAM_ID,
+            b"vault unlocked",
+            vec![],
+        ))
+        .execute()
+        .unwrap();
+
+    result.assert_log_contains("vault unlocked").unwrap();
+    result.assert_log_not_contains("vault drained").unwrap();
+
+    assert!(matches!(
+        result.assert_log_contains("vault drained"),
+        Err(MolluskHelperError::LogAssertionFailed(_))
+    ));
+    assert!(matches!(
+        result.assert_log_not_contains("unlocked"),
+        Err(MolluskHelperError::LogAssertionFailed(_))
+    ));
+}
+
+#[test]
+fn test_compute_units_do_not_carry_across_transactions() {
+    let mut ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+    let transfer = solana_system_interface::instruction::transfer(&alice, &bob, 100);
+
+    let first = ctx
+        .transaction()
+        .add_instruction(transfer.clone())
+        .add_instruction(Instruction::new_with_bytes(
+            MEMO_PROGRAM_ID,
+            b"warm up",
+            vec![],
+        ))
+        .with_compute_unit_limit(100_000)
+        .execute()
+        .unwrap();
+    ctx.reset_compute_meter();
+    let second = ctx
+        .transaction()
+        .add_instruction(transfer)
+        .execute()
+        .unwrap();
+
+    assert_eq!(
+        second.total_compute_units,
+        first.compute_units_per_instruction()[0]
+    );
+    assert!(second.total_compute_units < first.total_compute_units);
+}
+
+#[test]
+fn test_extract_anchor_events() {
+    let program_id = Pubkey::new_unique();
+    let ctx = MolluskContextHelper::new(&program_id, TEST_PROGRAM_ELF);
+
+    let discriminator = [64u8, 198, 205, 232, 38, 8, 113, 226];
+    let mut event = discriminator.to_vec();
+    event.extend_from_slice(&42u64.to_le_bytes());
+
+    let log_only = Instruction::new_with_bytes(program_id, &[0, b'h', b'i'], vec![]);
+    let result = ctx
+        .transaction()
+        .add_instruction(log_only)
+        .execute()
+        .unwrap();
+    assert!(result.extract_anchor_events().is_empty());
+
+    let mut data = vec![1u8];
+    data.extend_from_slice(&event);
+    let emit = Instruction::new_with_bytes(program_id, &data, vec![]);
+    let result = ctx.transaction().add_instruction(emit).execute().unwrap();
+    let events = result.extract_anchor_events();
+    assert_eq!(events, vec![event.clone()]);
+    assert_eq!(events[0][..8], discriminator);
+    assert_eq!(u64::from_le_bytes(events[0][8..].try_into().unwrap()), 42);
+
+    let logs = vec![
+        "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+        "Program log: Instruction: Deposit".to_string(),
+        "Program data: QMbN6CYIceIqAAAAAAAAAA==".to_string(),
+        "Program data: not-base64!".to_string(),
+        "Program 11111111111111111111111111111111 success".to_string(),
+    ];
+    let events = MolluskContextHelper::anchor_events_from_logs(&logs);
+    assert_eq!(events, vec![event]);
+    assert_eq!(events[0][..8], discriminator);
+}
+
+#[test]
+fn test_execute_versioned_with_lookup_table() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let payer = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    let carol = Pubkey::new_unique();
+    ctx.fund_account(&payer, 1_000_000);
+    ctx.fund_account(&bob, 0);
+    ctx.fund_account(&carol, 0);
+
+    let table =
+        MolluskContextHelper::create_lookup_table_account(Pubkey::new_unique(), vec![bob, carol]);
+    let instructions = vec![
+        solana_system_interface::instruction::transfer(&payer, &bob, 1_000),
+        solana_system_interface::instruction::transfer(&payer, &carol, 2_000),
+    ];
+
+    let (with_table, _, _) = MolluskContextHelper::build_versioned_tx_size(
+        &payer,
+        &instructions,
+        std::slice::from_ref(&table),
+    );
+    let (without_table, _, _) =
+        MolluskContextHelper::build_versioned_tx_size(&payer, &instructions, &[]);
+    assert!(with_table < without_table);
+
+    let result = ctx
+        .execute_versioned(&payer, &instructions, &[table])
+        .unwrap();
+    assert_eq!(result.instruction_results.len(), 2);
+    assert_eq!(ctx.get_balance(&bob), Some(1_000));
+    assert_eq!(ctx.get_balance(&carol), Some(2_000));
+    assert_eq!(ctx.get_balance(&payer), Some(997_000));
+}
+
+#[test]
+fn test_token_accounts_are_created_rent_exempt() {
+    let ctx = MolluskContextHelper::new_without_program();
+    let rent = mollusk_svm::sysvar::Sysvars::default().rent;
+
+    for len in [0, 82, 165, 1_000] {
+        assert_eq!(
+            ctx.minimum_balance_for_rent_exemption(len),
+            rent.minimum_balance(len)
+        );
+    }
+
+    let mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    ctx.create_mint(&mint, &owner, 6);
+    ctx.create_token_account(&token_account, &mint, &owner, 10);
+
+    assert_eq!(ctx.get_balance(&mint), Some(rent.minimum_balance(82)));
+    assert_eq!(
+        ctx.get_balance(&token_account),
+        Some(rent.minimum_balance(165))
+    );
+
+    let funded = Pubkey::new_unique();
+    ctx.fund_rent_exempt(&funded, 200);
+    assert_eq!(ctx.get_balance(&funded), Some(rent.minimum_balance(200)));
+}
+
+#[test]
+fn test_for_each_slot_accrues_interest() {
+    let mut ctx = MolluskContextHelper::new_without_program();
+
+    let pool = Pubkey::new_unique();
+    let depositor = Pubkey::new_unique();
+    ctx.fund_account(&pool, 1_000_000);
+    ctx.fund_account(&depositor, 10_000);
+
+    let mut visited = Vec::new();
+    ctx.for_each_slot(100, 150, 10, |ctx, slot| {
+        assert_eq!(ctx.sysvars_summary().slot, slot);
+        let balance = ctx.get_balance(&depositor).unwrap();
+        ctx.transfer_sol(&pool, &depositor, balance / 100).unwrap();
+        visited.push(slot);
+    });
+
+    assert_eq!(visited, vec![100, 110, 120, 130, 140]);
+    let mut expected = 10_000u64;
+    for _ in 0..5 {
+        expected += expected / 100;
+    }
+    assert_eq!(ctx.get_balance(&depositor), Some(expected));
+}
+
+#[test]
+fn test_execute_expect_instruction_error() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let mut unsigned = solana_system_interface::instruction::transfer(&alice, &bob, 1_000);
+    unsigned.accounts[0].is_signer = false;
+
+    ctx.transaction()
+        .add_instruction(unsigned.clone())
+        .execute_expect_instruction_error(InstructionError::MissingRequiredSignature)
+        .unwrap();
+
+    assert!(matches!(
+        ctx.transaction()
+            .add_instruction(unsigned)
+            .execute_expect_instruction_error(InstructionError::InsufficientFunds),
+        Err(MolluskHelperError::AssertionFailed(_))
+    ));
+    assert!(ctx
+        .transaction()
+        .add_instruction(solana_system_interface::instruction::transfer(
+            &alice, &bob, 1_000,
+        ))
+        .execute_expect_instruction_error(InstructionError::MissingRequiredSignature)
+        .is_err());
+    assert_eq!(ctx.get_balance(&bob), Some(1_000));
+}
+
+#[test]
+fn test_set_rent() {
+    let mut ctx = MolluskContextHelper::new_without_program();
+
+    let default_minimum = ctx.minimum_balance_for_rent_exemption(100);
+    assert_eq!(ctx.get_rent(), Rent::default());
+
+    ctx.set_rent(Rent::with_lamports_per_byte(10));
+    assert_eq!(ctx.get_rent(), Rent::with_lamports_per_byte(10));
+    assert_eq!(
+        ctx.minimum_balance_for_rent_exemption(100),
+        (128 + 100) * 10
+    );
+    assert_ne!(ctx.minimum_balance_for_rent_exemption(100), default_minimum);
+    assert_eq!(ctx.sysvars_summary().rent_exempt_lamports_per_byte, 10);
+}
+
+#[test]
+fn test_slot_and_epoch_accessors() {
+    let mut ctx = MolluskContextHelper::new_without_program();
+
+    ctx.warp_to_slot(1_234);
+    assert_eq!(ctx.current_slot(), 1_234);
+
+    let target = ctx.current_epoch() + 3;
+    ctx.warp_to_epoch(target);
+    assert_eq!(ctx.current_epoch(), target);
+
+    let first_slot = ctx.current_slot();
+    let mut fork = ctx.fork();
+    fork.warp_to_slot(first_slot - 1);
+    assert_eq!(fork.current_epoch(), target - 1);
+}
+
+#[test]
+fn test_new_with_cached_program() {
+    let program_id = Pubkey::new_unique();
+    let mut cache = ProgramCache::new();
+    cache.add_program(&program_id, mollusk_svm_programs_memo::memo::ELF);
+    assert!(cache.contains(&program_id));
+
+    let contexts = [&b"first context"[..], &b"second context"[..]].map(|memo| {
+        let ctx = MolluskContextHelper::new_with_cached_program(&cache, &program_id).unwrap();
+        let ix = Instruction::new_with_bytes(program_id, memo, vec![]);
+        let (_, logs) = ctx.process_instruction_with_logs(&ix).unwrap();
+        let memo = std::str::from_utf8(memo).unwrap();
+        assert!(logs.iter().any(|line| line.contains(memo)));
+        ctx
+    });
+    assert!(std::sync::Arc::ptr_eq(
+        &contexts[0].program_elf(&program_id).unwrap(),
+        &contexts[1].program_elf(&program_id).unwrap()
+    ));
+
+    assert!(matches!(
+        MolluskContextHelper::new_with_cached_program(&cache, &Pubkey::new_unique()),
+        Err(MolluskHelperError::ProgramNotLoaded(_))
+    ));
+}
+
+#[test]
+fn test_assert_instruction_count() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let result = ctx
+        .transaction()
+        .add_instructions(
+            ["one", "two", "three"]
+                .map(|memo| Instruction::new_with_bytes(MEMO_PROGRAM_ID, memo.as_bytes(), vec![])),
+        )
+        .execute()
+        .unwrap();
+
+    result.assert_instruction_count(3).unwrap();
+    assert!(matches!(
+        result.assert_instruction_count(2),
+        Err(MolluskHelperError::AssertionFailed(_))
+    ));
+}
+
+#[test]
+fn test_set_slot_hash_is_visible_in_sysvar_account() {
+    let mut ctx = MolluskContextHelper::new_without_program();
+
+    ctx.warp_to_slot(50);
+    assert_eq!(ctx.get_slot_hash(49), Some(solana_hash::Hash::default()));
+
+    let known = solana_hash::Hash::new_from_array([7u8; 32]);
+    ctx.set_slot_hash(49, known);
+    assert_eq!(ctx.get_slot_hash(49), Some(known));
+    assert_eq!(ctx.get_slot_hash(50), None);
+
+    let sysvar = solana_pubkey::pubkey!("SysvarS1otHashes111111111111111111111111111");
+    let account = ctx.get_account(&sysvar).unwrap();
+    let entries: Vec<(u64, [u8; 32])> = bincode::deserialize(&account.data).unwrap();
+    assert!(entries.contains(&(49, [7u8; 32])));
+}
+
+#[test]
+fn test_fund_accounts_and_add_accounts_in_bulk() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let entries: Vec<(Pubkey, u64)> = (0..100u64)
+        .map(|i| (Pubkey::new_unique(), 1_000 + i))
+        .collect();
+    ctx.fund_accounts(&entries);
+
+    for (pubkey, lamports) in &entries {
+        assert_eq!(ctx.get_balance(pubkey), Some(*lamports));
+    }
+
+    let owner = Pubkey::new_unique();
+    let data_accounts: Vec<(Pubkey, Account)> = (0..3u8)
+        .map(|i| {
+            (
+                Pubkey::new_unique(),
+                Account::new(500, usize::from(i), &owner),
+            )
+        })
+        .collect();
+    ctx.add_accounts(data_accounts.clone());
+
+    for (pubkey, account) in &data_accounts {
+        assert_eq!(ctx.get_account(pubkey).as_ref(), Some(account));
+    }
+    assert_eq!(ctx.accounts_owned_by(&owner).len(), 3);
+}
+
+#[test]
+fn test_transfer_tokens_with_program() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let authority = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+
+    for program in [TokenProgram::Legacy, TokenProgram::Token2022] {
+        let mint = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+
+        match program {
+            TokenProgram::Legacy => {
+                ctx.create_mint(&mint, &authority, 6);
+                ctx.create_token_account(&source, &mint, &authority, 0);
+                ctx.create_token_account(&destination, &mint, &recipient, 0);
+            }
+            TokenProgram::Token2022 => {
+                ctx.create_mint_2022(&mint, &authority, 6);
+                ctx.create_token_account_2022(&source, &mint, &authority, 0);
+                ctx.create_token_account_2022(&destination, &mint, &recipient, 0);
+            }
+        }
+
+        ctx.mint_to_with_program(&mint, &source, &authority, 1_000, program)
+            .unwrap();
+        ctx.transfer_tokens_with_program(&source, &destination, &authority, 250, program)
+            .unwrap();
+
+        assert_eq!(ctx.get_token_balance(&source).unwrap(), 750);
+        assert_eq!(ctx.get_token_balance(&destination).unwrap(), 250);
+        assert_eq!(ctx.get_account(&destination).unwrap().owner, program.id());
+    }
+}
+
+#[test]
+fn test_process_instruction_capturing() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let ix = solana_system_interface::instruction::transfer(&alice, &bob, 250_000);
+    let (before, after) = ctx.process_instruction_capturing(&ix, &alice).unwrap();
+
+    assert_eq!(before.lamports - after.lamports, 250_000);
+    assert_eq!(ctx.get_balance(&alice), Some(after.lamports));
+    assert_eq!(ctx.get_balance(&bob), Some(250_000));
+}
+
+#[test]
+fn test_transfer_sol_with_balances() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+
+    let (alice_balance, bob_balance) = ctx
+        .transfer_sol_with_balances(&alice, &bob, 500_000)
+        .unwrap();
+    assert_eq!(alice_balance, 500_000);
+    assert_eq!(bob_balance, 500_000);
+
+    let missing = Pubkey::new_unique();
+    assert!(ctx.transfer_sol_with_balances(&missing, &bob, 1).is_err());
+}
+
+#[test]
+fn test_diff_since_checkpoint() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    let carol = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000_000);
+    ctx.fund_account(&bob, 0);
+    ctx.fund_account(&carol, 42);
+
+    ctx.checkpoint("funded").unwrap();
+    ctx.transfer_sol(&alice, &bob, 300_000).unwrap();
+
+    let diffs = ctx.diff_since_checkpoint("funded").unwrap();
+    let lamport_diffs: Vec<_> = diffs
+        .iter()
+        .filter(|d| d.old_lamports != d.new_lamports)
+        .collect();
+    assert_eq!(lamport_diffs.len(), 2);
+    assert!(lamport_diffs.iter().all(|d| !d.data_changed));
+    assert!(lamport_diffs.iter().any(|d| d.pubkey == alice));
+    assert!(lamport_diffs.iter().any(|d| d.pubkey == bob));
+
+    assert!(ctx.diff_since_checkpoint("missing").is_err());
+}
+
+#[test]
+fn test_token_setup_builder() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let authority = Pubkey::new_unique();
+    let wallet = Pubkey::new_unique();
+
+    let (mint, ata) = TokenSetup::new(&ctx)
+        .mint(authority, 9)
+        .holder(wallet, 1_000)
+        .build()
+        .unwrap();
+
+    assert_eq!(ata, ctx.get_associated_token_address(&wallet, &mint));
+    assert_eq!(ctx.get_token_balance(&ata).unwrap(), 1_000);
+    let mint_info = ctx.get_mint_info(&mint).unwrap();
+    assert_eq!(mint_info.decimals, 9);
+    assert_eq!(mint_info.supply, 1_000);
+
+    assert!(matches!(
+        TokenSetup::new(&ctx).holder(wallet, 1).build(),
+        Err(MolluskHelperError::InvalidTokenSetup(_))
+    ));
+}
+
+#[test]
+fn test_assert_store_empty() {
+    let ctx = MolluskContextHelper::new_without_program();
+    ctx.assert_store_empty().unwrap();
+
+    let alice = Pubkey::new_unique();
+    ctx.fund_account(&alice, 1_000);
+
+    match ctx.assert_store_empty() {
+        Err(MolluskHelperError::AssertionFailed(msg)) => {
+            assert!(msg.contains(&alice.to_string()))
+        }
+        other => panic!("expected AssertionFailed, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_add_account_from_json() {
+    let ctx = MolluskContextHelper::new_without_program();
+
+    let pubkey = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let json = format!(
+        r#"{{
+            "pubkey": "{pubkey}",
+            "account": {{
+                "lamports": 1461600,
+                "data": ["AQIDBA==", "base64"],
+                "owner": "{owner}",
+                "executable": false,
+                "rentEpoch": 18446744073709551615,
+                "space": 4
+            }}
+        }}"#
+    );
+
+    ctx.add_account_from_json(&pubkey, &json).unwrap();
+
+    let account = ctx.get_account(&pubkey).unwrap();
+    assert_eq!(account.lamports, 1_461_600);
+    assert_eq!(account.data, vec![1, 2, 3, 4]);
+    assert_eq!(account.owner, owner);
+    assert!(!account.executable);
+    assert_eq!(account.rent_epoch, u64::MAX);
+
+    assert!(matches!(
+        ctx.add_account_from_json(&pubkey, "not json"),
+        Err(MolluskHelperError::AccountDecode(_))
+    ));
+    assert!(matches!(
+        ctx.add_account_from_json(&pubkey, r#"{"lamports": 1}"#),
+        Err(MolluskHelperError::AccountDecode(_))
+    ));
+}